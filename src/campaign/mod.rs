@@ -0,0 +1,161 @@
+pub mod save_data;
+
+use save_data::SaveData;
+use serde::Deserialize;
+use std::{
+    fs,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+/// Name of the manifest in the levels directory, which is not a level itself
+const MANIFEST_NAME: &str = "campaign";
+
+/// When a campaign level becomes playable
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UnlockRule {
+    /// Unlocked once the level before it in the campaign is completed
+    #[default]
+    Previous,
+    Always,
+    /// Unlocked once all of the listed levels are completed
+    Completed(Vec<String>),
+}
+
+#[derive(Deserialize)]
+pub struct CampaignLevel {
+    pub name: String,
+    #[serde(default)]
+    pub unlock: UnlockRule,
+}
+
+/// The order of the levels and the rules for unlocking them, read from `levels/campaign.json`.
+///
+/// Levels that are not part of the campaign, e.g. ones made in the editor, are listed after
+/// the campaign levels and are always unlocked.
+#[derive(Deserialize, Default)]
+pub struct Campaign {
+    pub levels: Vec<CampaignLevel>,
+}
+
+impl Campaign {
+    pub fn load() -> Self {
+        let mut campaign = match File::open(level_path(MANIFEST_NAME)) {
+            Ok(file) => serde_json::from_reader(file).unwrap_or_else(|e| {
+                eprintln!("Could not read campaign manifest: {}", e);
+                Campaign::default()
+            }),
+            Err(_) => Campaign::default(),
+        };
+
+        // Leave out missing levels, so they are not listed and the levels after them can be unlocked
+        campaign.levels.retain(|level| {
+            let exists = level_path(&level.name).exists();
+            if !exists {
+                eprintln!("Campaign level {} does not exist", level.name);
+            }
+            exists
+        });
+
+        campaign
+    }
+
+    /// Get the names of all levels, with the campaign levels first in campaign order
+    pub fn level_order(&self) -> Vec<String> {
+        let mut other_levels: Vec<String> = get_all_level_names()
+            .into_iter()
+            .filter(|name| !self.levels.iter().any(|level| &level.name == name))
+            .collect();
+        other_levels.sort();
+
+        self.levels
+            .iter()
+            .map(|level| level.name.to_owned())
+            .chain(other_levels)
+            .collect()
+    }
+
+    pub fn is_unlocked(&self, level_name: &str, save_data: &SaveData) -> bool {
+        let index = match self.levels.iter().position(|l| l.name == level_name) {
+            Some(index) => index,
+            None => return true,
+        };
+
+        match &self.levels[index].unlock {
+            UnlockRule::Always => true,
+            UnlockRule::Previous if index == 0 => true,
+            UnlockRule::Previous => save_data.is_completed(&self.levels[index - 1].name),
+            UnlockRule::Completed(names) => names.iter().all(|name| save_data.is_completed(name)),
+        }
+    }
+
+    /// Find the level following the given one
+    pub fn next_level(&self, level_name: &str) -> Option<String> {
+        let order = self.level_order();
+        let index = order.iter().position(|name| name == level_name)?;
+        order.get(index + 1).cloned()
+    }
+}
+
+fn level_path(level_name: &str) -> PathBuf {
+    let mut path = Path::new("levels").join(level_name);
+    path.set_extension("json");
+    path
+}
+
+/// Get the names of the level files, or none if the levels directory can not be read
+fn get_all_level_names() -> Vec<String> {
+    let entries = match fs::read_dir("levels") {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Could not read levels: {}", e);
+            return Vec::new();
+        }
+    };
+
+    entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".json"))
+        .map(|name| name.replace(".json", ""))
+        .filter(|name| name != MANIFEST_NAME)
+        .collect()
+}
+
+#[cfg(test)]
+mod campaign_tests {
+    use super::{save_data::SaveData, Campaign, CampaignLevel, UnlockRule};
+    use crate::game::stats::RunStats;
+
+    fn campaign() -> Campaign {
+        let level = |name: &str, unlock| CampaignLevel {
+            name: name.to_owned(),
+            unlock,
+        };
+        Campaign {
+            levels: vec![
+                level("first", UnlockRule::Previous),
+                level("second", UnlockRule::Previous),
+                level("bonus", UnlockRule::Completed(vec!["first".to_owned()])),
+            ],
+        }
+    }
+
+    #[test]
+    fn unlock_previous() {
+        let campaign = campaign();
+        let mut save_data = SaveData::default();
+        assert!(campaign.is_unlocked("first", &save_data));
+        assert!(!campaign.is_unlocked("second", &save_data));
+        assert!(!campaign.is_unlocked("bonus", &save_data));
+
+        save_data.record_completion("first", &RunStats::new());
+        assert!(campaign.is_unlocked("second", &save_data));
+        assert!(campaign.is_unlocked("bonus", &save_data));
+    }
+
+    #[test]
+    fn custom_levels_are_unlocked() {
+        assert!(campaign().is_unlocked("my_level", &SaveData::default()));
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{
+    config,
+    game::stats::{Rank, RunStats},
+};
+use ggez::Context;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, io, path::PathBuf};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LevelRecord {
+    pub best_time: f32,
+    pub best_rank: Rank,
+}
+
+/// Campaign progress, stored as `save.json` in the user data directory
+#[derive(Default, Serialize, Deserialize)]
+pub struct SaveData {
+    pub completed_levels: HashMap<String, LevelRecord>,
+}
+
+impl SaveData {
+    fn path(ctx: &Context) -> PathBuf {
+        ctx.fs.user_data_dir().join("save.json")
+    }
+
+    pub fn load(ctx: &Context) -> Self {
+        config::load_json(&Self::path(ctx))
+    }
+
+    pub fn save(&self, ctx: &Context) -> io::Result<()> {
+        config::save_json(&Self::path(ctx), self)
+    }
+
+    pub fn is_completed(&self, level_name: &str) -> bool {
+        self.completed_levels.contains_key(level_name)
+    }
+
+    pub fn record(&self, level_name: &str) -> Option<&LevelRecord> {
+        self.completed_levels.get(level_name)
+    }
+
+    /// Mark the level as completed, keeping the best time and rank seen so far
+    pub fn record_completion(&mut self, level_name: &str, stats: &RunStats) {
+        let time = stats.time;
+        let rank = stats.rank();
+
+        self.completed_levels
+            .entry(level_name.to_owned())
+            .and_modify(|record| {
+                record.best_time = record.best_time.min(time);
+                record.best_rank = record.best_rank.max(rank);
+            })
+            .or_insert(LevelRecord {
+                best_time: time,
+                best_rank: rank,
+            });
+    }
+}
@@ -0,0 +1,24 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fs, fs::File, io, path::Path};
+
+/// Read a JSON file from the user config or data directory, falling back to the default when
+/// the file does not exist or can not be read
+pub fn load_json<T: Default + DeserializeOwned>(path: &Path) -> T {
+    match File::open(path) {
+        Ok(file) => serde_json::from_reader(file).unwrap_or_else(|e| {
+            eprintln!("Could not read {}: {}", path.display(), e);
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// Write a JSON file, creating its directory if needed
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+
+    let file = File::create(path)?;
+    serde_json::to_writer_pretty(file, value).map_err(io::Error::from)
+}
@@ -0,0 +1,390 @@
+use crate::config;
+use ggez::{
+    input::{
+        gamepad::gilrs::{Axis, Button as GamepadButton},
+        keyboard::KeyCode,
+    },
+    Context,
+};
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, io, path::PathBuf};
+
+/// Stick deflections smaller than the deadzone are ignored, since sticks rarely rest exactly at zero
+pub const DEFAULT_STICK_DEADZONE: f32 = 0.2;
+pub const MAX_STICK_DEADZONE: f32 = 0.9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Sprint,
+    Interact,
+    Throw,
+    Pause,
+    EditorSnap,
+    EditorCreateObstacle,
+    EditorDelete,
+    EditorExit,
+    EditorCopy,
+    EditorPaste,
+    EditorDuplicate,
+    EditorUndo,
+    EditorRedo,
+    MenuConfirm,
+    MenuBack,
+}
+
+/// Where an action is used, actions used in different places may share inputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionContext {
+    Game,
+    Editor,
+    /// Menus are also navigated with Enter, Escape, the arrow keys and the D-pad, so the menu
+    /// actions are only bound to gamepad buttons
+    Menu,
+}
+
+impl Action {
+    pub const ALL: [Action; 19] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Sprint,
+        Action::Interact,
+        Action::Throw,
+        Action::Pause,
+        Action::EditorSnap,
+        Action::EditorCreateObstacle,
+        Action::EditorDelete,
+        Action::EditorExit,
+        Action::EditorCopy,
+        Action::EditorPaste,
+        Action::EditorDuplicate,
+        Action::EditorUndo,
+        Action::EditorRedo,
+        Action::MenuConfirm,
+        Action::MenuBack,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Sprint => "Sprint",
+            Action::Interact => "Interact",
+            Action::Throw => "Throw",
+            Action::Pause => "Pause",
+            Action::EditorSnap => "Snap to grid",
+            Action::EditorCreateObstacle => "Create obstacle",
+            Action::EditorDelete => "Delete selected",
+            Action::EditorExit => "Exit editor",
+            Action::EditorCopy => "Copy",
+            Action::EditorPaste => "Paste",
+            Action::EditorDuplicate => "Duplicate",
+            Action::EditorUndo => "Undo",
+            Action::EditorRedo => "Redo",
+            Action::MenuConfirm => "Menu confirm",
+            Action::MenuBack => "Menu back",
+        }
+    }
+
+    pub fn context(self) -> ActionContext {
+        match self {
+            Action::EditorSnap
+            | Action::EditorCreateObstacle
+            | Action::EditorDelete
+            | Action::EditorExit
+            | Action::EditorCopy
+            | Action::EditorPaste
+            | Action::EditorDuplicate
+            | Action::EditorUndo
+            | Action::EditorRedo => ActionContext::Editor,
+            Action::MenuConfirm | Action::MenuBack => ActionContext::Menu,
+            _ => ActionContext::Game,
+        }
+    }
+
+    /// Shortcuts that are pressed together with Ctrl (or Cmd), like in other programs, so they
+    /// may share keys with the actions that are pressed alone
+    pub fn needs_ctrl(self) -> bool {
+        matches!(
+            self,
+            Action::EditorCopy
+                | Action::EditorPaste
+                | Action::EditorDuplicate
+                | Action::EditorUndo
+                | Action::EditorRedo
+        )
+    }
+
+    fn default_binding(self) -> Binding {
+        let (key, button) = match self {
+            Action::MoveUp => (Some(KeyCode::W), Some(GamepadButton::DPadUp)),
+            Action::MoveDown => (Some(KeyCode::S), Some(GamepadButton::DPadDown)),
+            Action::MoveLeft => (Some(KeyCode::A), Some(GamepadButton::DPadLeft)),
+            Action::MoveRight => (Some(KeyCode::D), Some(GamepadButton::DPadRight)),
+            Action::Sprint => (Some(KeyCode::LShift), Some(GamepadButton::RightTrigger)),
+            Action::Interact => (Some(KeyCode::E), Some(GamepadButton::South)),
+            Action::Throw => (Some(KeyCode::Q), Some(GamepadButton::West)),
+            Action::Pause => (Some(KeyCode::Escape), Some(GamepadButton::Start)),
+            Action::EditorSnap => (Some(KeyCode::LControl), None),
+            Action::EditorCreateObstacle => (Some(KeyCode::O), None),
+            Action::EditorDelete => (Some(KeyCode::Delete), None),
+            Action::EditorExit => (Some(KeyCode::Escape), None),
+            Action::EditorCopy => (Some(KeyCode::C), None),
+            Action::EditorPaste => (Some(KeyCode::V), None),
+            Action::EditorDuplicate => (Some(KeyCode::D), None),
+            Action::EditorUndo => (Some(KeyCode::Z), None),
+            Action::EditorRedo => (Some(KeyCode::Y), None),
+            Action::MenuConfirm => (None, Some(GamepadButton::South)),
+            Action::MenuBack => (None, Some(GamepadButton::East)),
+        };
+        Binding { key, button }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Binding {
+    pub key: Option<KeyCode>,
+    #[serde(with = "gamepad_button_name")]
+    pub button: Option<GamepadButton>,
+}
+
+/// Maps actions to keys and gamepad buttons, stored as `controls.json` in the user config directory
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Controls {
+    bindings: HashMap<Action, Binding>,
+    #[serde(default = "default_stick_deadzone")]
+    stick_deadzone: f32,
+}
+
+fn default_stick_deadzone() -> f32 {
+    DEFAULT_STICK_DEADZONE
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Controls {
+            bindings: HashMap::new(),
+            stick_deadzone: DEFAULT_STICK_DEADZONE,
+        }
+    }
+}
+
+impl Controls {
+    fn path(ctx: &Context) -> PathBuf {
+        ctx.fs.user_config_dir().join("controls.json")
+    }
+
+    pub fn load(ctx: &Context) -> Self {
+        config::load_json(&Self::path(ctx))
+    }
+
+    pub fn save(&self, ctx: &Context) -> io::Result<()> {
+        config::save_json(&Self::path(ctx), self)
+    }
+
+    /// Get the binding of an action, falling back to the default if it was never rebound
+    pub fn binding(&self, action: Action) -> Binding {
+        self.bindings
+            .get(&action)
+            .cloned()
+            .unwrap_or_else(|| action.default_binding())
+    }
+
+    pub fn matches_key(&self, action: Action, key_code: KeyCode) -> bool {
+        self.binding(action).key == Some(key_code)
+    }
+
+    pub fn matches_button(&self, action: Action, button: GamepadButton) -> bool {
+        self.binding(action).button == Some(button)
+    }
+
+    /// Check whether the action is held down on the keyboard or any connected gamepad
+    pub fn is_pressed(&self, ctx: &Context, action: Action) -> bool {
+        let binding = self.binding(action);
+
+        let key_pressed = binding
+            .key
+            .is_some_and(|key| ctx.keyboard.is_key_pressed(key));
+        let button_pressed = binding.button.is_some_and(|button| {
+            ctx.gamepad
+                .gamepads()
+                .any(|(_, gamepad)| gamepad.is_pressed(button))
+        });
+
+        key_pressed || button_pressed
+    }
+
+    pub fn stick_deadzone(&self) -> f32 {
+        self.stick_deadzone
+    }
+
+    pub fn set_stick_deadzone(&mut self, deadzone: f32) {
+        self.stick_deadzone = deadzone.clamp(0.0, MAX_STICK_DEADZONE);
+    }
+
+    /// Get the left stick direction of the first gamepad that is pushed past the deadzone,
+    /// with a magnitude of at most 1.0 and y pointing down like the screen coordinates
+    pub fn analog_movement(&self, ctx: &Context) -> Vector2<f32> {
+        ctx.gamepad
+            .gamepads()
+            .map(|(_, gamepad)| {
+                Vector2::new(
+                    gamepad.value(Axis::LeftStickX),
+                    -gamepad.value(Axis::LeftStickY),
+                )
+            })
+            .find(|stick| stick.magnitude() > self.stick_deadzone)
+            .map(|stick| {
+                // Rescale so walking speed starts from zero at the edge of the deadzone
+                let magnitude = stick.magnitude();
+                let scaled =
+                    ((magnitude - self.stick_deadzone) / (1.0 - self.stick_deadzone)).min(1.0);
+                stick / magnitude * scaled
+            })
+            .unwrap_or_else(Vector2::zeros)
+    }
+
+    /// Find another action in the same context that is already bound to the input
+    fn find_conflict(&self, action: Action, is_bound: impl Fn(&Binding) -> bool) -> Option<Action> {
+        Action::ALL.into_iter().find(|&other| {
+            other != action
+                && other.context() == action.context()
+                && other.needs_ctrl() == action.needs_ctrl()
+                && is_bound(&self.binding(other))
+        })
+    }
+
+    /// Bind the key to the action, or return the action that already uses it
+    pub fn bind_key(&mut self, action: Action, key_code: KeyCode) -> Result<(), Action> {
+        if let Some(conflict) = self.find_conflict(action, |b| b.key == Some(key_code)) {
+            return Err(conflict);
+        }
+
+        let mut binding = self.binding(action);
+        binding.key = Some(key_code);
+        self.bindings.insert(action, binding);
+        Ok(())
+    }
+
+    /// Bind the gamepad button to the action, or return the action that already uses it
+    pub fn bind_button(&mut self, action: Action, button: GamepadButton) -> Result<(), Action> {
+        if let Some(conflict) = self.find_conflict(action, |b| b.button == Some(button)) {
+            return Err(conflict);
+        }
+
+        let mut binding = self.binding(action);
+        binding.button = Some(button);
+        self.bindings.insert(action, binding);
+        Ok(())
+    }
+}
+
+/// gilrs only implements serde behind a feature that ggez does not enable,
+/// so gamepad buttons are stored by name instead
+mod gamepad_button_name {
+    use super::GamepadButton;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    const BUTTONS: [GamepadButton; 19] = [
+        GamepadButton::South,
+        GamepadButton::East,
+        GamepadButton::North,
+        GamepadButton::West,
+        GamepadButton::C,
+        GamepadButton::Z,
+        GamepadButton::LeftTrigger,
+        GamepadButton::LeftTrigger2,
+        GamepadButton::RightTrigger,
+        GamepadButton::RightTrigger2,
+        GamepadButton::Select,
+        GamepadButton::Start,
+        GamepadButton::Mode,
+        GamepadButton::LeftThumb,
+        GamepadButton::RightThumb,
+        GamepadButton::DPadUp,
+        GamepadButton::DPadDown,
+        GamepadButton::DPadLeft,
+        GamepadButton::DPadRight,
+    ];
+
+    pub fn serialize<S: Serializer>(
+        button: &Option<GamepadButton>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        button
+            .map(|button| format!("{:?}", button))
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<GamepadButton>, D::Error> {
+        let name = Option::<String>::deserialize(deserializer)?;
+        Ok(name.and_then(|name| {
+            BUTTONS
+                .into_iter()
+                .find(|button| format!("{:?}", button) == name)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod controls_tests {
+    use super::{Action, Controls, GamepadButton, KeyCode};
+
+    #[test]
+    fn rebind_key() {
+        let mut controls = Controls::default();
+        assert!(controls.bind_key(Action::Interact, KeyCode::F).is_ok());
+        assert!(controls.matches_key(Action::Interact, KeyCode::F));
+        assert!(!controls.matches_key(Action::Interact, KeyCode::E));
+    }
+
+    #[test]
+    fn conflicting_key() {
+        let mut controls = Controls::default();
+        let result = controls.bind_key(Action::Interact, KeyCode::W);
+        assert_eq!(result, Err(Action::MoveUp));
+        assert!(controls.matches_key(Action::Interact, KeyCode::E));
+    }
+
+    #[test]
+    fn stick_deadzone_is_clamped() {
+        let mut controls = Controls::default();
+        controls.set_stick_deadzone(2.0);
+        assert_eq!(controls.stick_deadzone(), super::MAX_STICK_DEADZONE);
+    }
+
+    #[test]
+    fn editor_actions_share_keys() {
+        let mut controls = Controls::default();
+        assert!(controls.bind_key(Action::EditorDelete, KeyCode::W).is_ok());
+    }
+
+    #[test]
+    fn menu_buttons_share_game_buttons() {
+        let mut controls = Controls::default();
+        let result = controls.bind_button(Action::MenuBack, GamepadButton::South);
+        assert_eq!(result, Err(Action::MenuConfirm));
+        assert!(controls
+            .bind_button(Action::MenuBack, GamepadButton::West)
+            .is_ok());
+        assert!(controls.matches_button(Action::MenuBack, GamepadButton::West));
+    }
+
+    #[test]
+    fn ctrl_shortcuts_share_keys() {
+        let mut controls = Controls::default();
+        assert!(controls.bind_key(Action::EditorCopy, KeyCode::O).is_ok());
+        let result = controls.bind_key(Action::EditorCopy, KeyCode::V);
+        assert_eq!(result, Err(Action::EditorPaste));
+    }
+}
@@ -0,0 +1,218 @@
+use super::{PolygonType, SelectionObject};
+use crate::game::{
+    actor::Actor, controller::Controller, game_map::LightZone, level_info::GuardSettings,
+    polygon::Polygon, Game,
+};
+use nalgebra::{Point2, Vector2};
+use serde::{Deserialize, Serialize};
+
+type Point = (f32, f32);
+
+/// Objects copied in the editor, written to the clipboard with the same fields as level files,
+/// so they can be pasted into other levels and other running editors
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CopiedObjects {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub guard_data: Vec<(Point, Vec<Point>)>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub guard_settings: Vec<GuardSettings>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub obstacle_data: Vec<Vec<Point>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub end_area_data: Vec<Vec<Point>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub light_zone_data: Vec<(f32, Vec<Point>)>,
+}
+
+fn to_points(verts: &[Point2<f32>]) -> Vec<Point> {
+    verts.iter().map(|v| (v.x, v.y)).collect()
+}
+
+fn to_verts(points: &[Point]) -> Vec<Point2<f32>> {
+    points.iter().map(|&(x, y)| Point2::new(x, y)).collect()
+}
+
+impl CopiedObjects {
+    /// Copy the selected guards and shapes, the player and single vertices can not be copied
+    pub fn from_selection(game: &Game, selection: &[SelectionObject]) -> Self {
+        let mut copied = CopiedObjects::default();
+
+        for object in selection {
+            match *object {
+                SelectionObject::Actor { index } => {
+                    let actor = &game.actors[index];
+                    if let Controller::Guard(guard) = &actor.controller {
+                        let pos = (actor.pos.x, actor.pos.y);
+                        copied
+                            .guard_data
+                            .push((pos, to_points(&guard.points.verts)));
+                        copied.guard_settings.push(actor.guard_settings());
+                    }
+                }
+                SelectionObject::Polygon { polygon_type } => {
+                    let points = to_points(&polygon_type.get(&game.game_map).verts);
+                    match polygon_type {
+                        PolygonType::Obstacle { .. } => copied.obstacle_data.push(points),
+                        PolygonType::EndArea { .. } => copied.end_area_data.push(points),
+                        PolygonType::LightZone { index } => {
+                            let light_level = game.game_map.light_zones[index].light_level;
+                            copied.light_zone_data.push((light_level, points));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        copied
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.guard_data.is_empty()
+            && self.obstacle_data.is_empty()
+            && self.end_area_data.is_empty()
+            && self.light_zone_data.is_empty()
+    }
+
+    /// Read objects from the clipboard, if it holds any
+    pub fn from_json(text: &str) -> Option<Self> {
+        serde_json::from_str::<CopiedObjects>(text)
+            .ok()
+            .filter(|copied| !copied.is_empty())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    fn points(&self) -> impl Iterator<Item = &Point> {
+        let guards = self
+            .guard_data
+            .iter()
+            .flat_map(|(pos, patrol)| std::iter::once(pos).chain(patrol));
+        let shapes = self
+            .obstacle_data
+            .iter()
+            .chain(&self.end_area_data)
+            .chain(self.light_zone_data.iter().map(|(_, points)| points))
+            .flatten();
+        guards.chain(shapes)
+    }
+
+    /// The center of the bounding box of everything that was copied
+    pub fn center(&self) -> Option<Point2<f32>> {
+        let mut points = self.points();
+        let &(x, y) = points.next()?;
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (x, y, x, y);
+        for &(x, y) in points {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        Some(Point2::new((min_x + max_x) / 2.0, (min_y + max_y) / 2.0))
+    }
+
+    /// Add the objects to the level moved by `offset`, returning them so they can be selected
+    pub fn add_to(&self, game: &mut Game, offset: Vector2<f32>) -> Vec<SelectionObject> {
+        let moved = |points: &[Point]| -> Vec<Point2<f32>> {
+            to_verts(points).into_iter().map(|v| v + offset).collect()
+        };
+        let mut added = Vec::new();
+
+        for (i, ((x, y), patrol)) in self.guard_data.iter().enumerate() {
+            let mut guard = Actor::new_guard(x + offset.x, y + offset.y, moved(patrol));
+            if let Some(settings) = self.guard_settings.get(i) {
+                guard.apply_guard_settings(settings);
+            }
+            added.push(SelectionObject::Actor {
+                index: game.actors.len(),
+            });
+            game.actors.push(guard);
+        }
+
+        let game_map = &mut game.game_map;
+        for points in &self.obstacle_data {
+            let polygon_type = PolygonType::Obstacle {
+                index: game_map.obstacles.len(),
+            };
+            added.push(SelectionObject::Polygon { polygon_type });
+            game_map.obstacles.push(Polygon::new(moved(points)));
+        }
+        for points in &self.end_area_data {
+            let polygon_type = PolygonType::EndArea {
+                index: game_map.end_areas.len(),
+            };
+            added.push(SelectionObject::Polygon { polygon_type });
+            game_map.end_areas.push(Polygon::new(moved(points)));
+        }
+        for (light_level, points) in &self.light_zone_data {
+            let polygon_type = PolygonType::LightZone {
+                index: game_map.light_zones.len(),
+            };
+            added.push(SelectionObject::Polygon { polygon_type });
+            let zone = LightZone::new(Polygon::new(moved(points)), *light_level);
+            game_map.light_zones.push(zone);
+        }
+
+        added
+    }
+}
+
+#[cfg(test)]
+mod copy_paste_tests {
+    use super::CopiedObjects;
+    use crate::{
+        editor::{PolygonType, SelectionObject},
+        game::{actor::Actor, polygon::Polygon, Game},
+    };
+    use nalgebra::{Point2, Vector2};
+
+    #[test]
+    fn pasted_objects_are_moved_and_selected() {
+        let mut game = Game::new();
+        let square = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(50.0, 0.0),
+            Point2::new(50.0, 50.0),
+            Point2::new(0.0, 50.0),
+        ];
+        game.game_map.obstacles.push(Polygon::new(square.clone()));
+        let mut guard = Actor::new_guard(25.0, 25.0, square);
+        guard.move_speed = 2.0;
+        game.actors.push(guard);
+
+        let selection = [
+            SelectionObject::Actor { index: 0 },
+            SelectionObject::Actor { index: 1 },
+            SelectionObject::Polygon {
+                polygon_type: PolygonType::Obstacle { index: 0 },
+            },
+        ];
+        let copied = CopiedObjects::from_selection(&game, &selection);
+        assert_eq!(copied.center(), Some(Point2::new(25.0, 25.0)));
+
+        // Going through the clipboard keeps everything but the player
+        let copied = CopiedObjects::from_json(&copied.to_json()).unwrap();
+        let added = copied.add_to(&mut game, Vector2::new(100.0, 0.0));
+        assert_eq!(
+            added,
+            vec![
+                SelectionObject::Actor { index: 2 },
+                SelectionObject::Polygon {
+                    polygon_type: PolygonType::Obstacle { index: 1 },
+                },
+            ]
+        );
+        assert_eq!(game.actors[2].pos, Point2::new(125.0, 25.0));
+        assert_eq!(game.actors[2].move_speed, 2.0);
+        assert_eq!(game.game_map.obstacles[1].verts[0], Point2::new(100.0, 0.0));
+    }
+
+    #[test]
+    fn other_text_is_not_pasted() {
+        assert!(CopiedObjects::from_json("hello").is_none());
+        assert!(CopiedObjects::from_json("{}").is_none());
+    }
+}
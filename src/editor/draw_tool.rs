@@ -0,0 +1,357 @@
+use super::PolygonType;
+use crate::game::{
+    game_map::{GameMap, LightZone, DEFAULT_LIGHT_LEVEL},
+    polygon::Polygon,
+};
+use nalgebra::{distance, Point2, Vector2};
+use std::f32::consts::TAU;
+
+/// How close a click has to be to a vertex to close the polygon
+const CLOSE_DISTANCE: f32 = 8.0;
+/// Cross products smaller than this count as collinear
+const EPSILON: f32 = 1e-3;
+
+/// The kind of shape drawn by clicking in the level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Polygon,
+    Rectangle,
+    /// A regular polygon, e.g. an octagon
+    Circle,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 3] = [Shape::Polygon, Shape::Rectangle, Shape::Circle];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Shape::Polygon => "Polygon",
+            Shape::Rectangle => "Rectangle",
+            Shape::Circle => "Circle",
+        }
+    }
+
+    /// How to draw the shape, shown when drawing starts
+    pub fn instructions(self) -> &'static str {
+        match self {
+            Shape::Polygon => "Click to place vertices, double-click or press Enter to finish",
+            Shape::Rectangle => "Click two opposite corners",
+            Shape::Circle => "Click the center, then a point on the edge",
+        }
+    }
+}
+
+/// What a drawn shape becomes when it is finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawTarget {
+    Obstacle,
+    LightZone,
+    EndArea,
+}
+
+impl DrawTarget {
+    /// Add the shape to the level, returning where it was added
+    pub fn add_to(self, game_map: &mut GameMap, verts: Vec<Point2<f32>>) -> PolygonType {
+        let polygon = Polygon::new(verts);
+        match self {
+            DrawTarget::Obstacle => {
+                game_map.obstacles.push(polygon);
+                PolygonType::Obstacle {
+                    index: game_map.obstacles.len() - 1,
+                }
+            }
+            DrawTarget::LightZone => {
+                game_map
+                    .light_zones
+                    .push(LightZone::new(polygon, DEFAULT_LIGHT_LEVEL));
+                PolygonType::LightZone {
+                    index: game_map.light_zones.len() - 1,
+                }
+            }
+            DrawTarget::EndArea => {
+                game_map.end_areas.push(polygon);
+                PolygonType::EndArea {
+                    index: game_map.end_areas.len() - 1,
+                }
+            }
+        }
+    }
+}
+
+/// A shape being drawn in the editor, which follows the mouse until it is finished
+pub struct ShapeDrawing {
+    pub target: DrawTarget,
+    shape: Shape,
+    /// Number of vertices of circles
+    sides: usize,
+    /// The clicked points, the vertices of polygons or the first corner or center of other shapes
+    points: Vec<Point2<f32>>,
+    cursor: Point2<f32>,
+}
+
+impl ShapeDrawing {
+    pub fn new(target: DrawTarget, shape: Shape, sides: usize, cursor: Point2<f32>) -> Self {
+        ShapeDrawing {
+            target,
+            shape,
+            sides: sides.max(3),
+            points: Vec::new(),
+            cursor,
+        }
+    }
+
+    pub fn move_cursor(&mut self, pos: Point2<f32>) {
+        self.cursor = pos;
+    }
+
+    pub fn cursor(&self) -> Point2<f32> {
+        self.cursor
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    pub fn points(&self) -> &[Point2<f32>] {
+        &self.points
+    }
+
+    /// The shape as it would be if the cursor was clicked, open while a polygon is drawn
+    pub fn outline(&self) -> Vec<Point2<f32>> {
+        let Some(&start) = self.points.first() else {
+            return Vec::new();
+        };
+
+        match self.shape {
+            Shape::Polygon => {
+                let mut verts = self.points.clone();
+                verts.push(self.cursor);
+                verts
+            }
+            Shape::Rectangle => vec![
+                start,
+                Point2::new(self.cursor.x, start.y),
+                self.cursor,
+                Point2::new(start.x, self.cursor.y),
+            ],
+            Shape::Circle => {
+                let radius = distance(&start, &self.cursor);
+                let offset = self.cursor - start;
+                let first_angle = offset.y.atan2(offset.x);
+                (0..self.sides)
+                    .map(|i| {
+                        let angle = first_angle + TAU * i as f32 / self.sides as f32;
+                        start + Vector2::new(angle.cos(), angle.sin()) * radius
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Whether clicking the cursor would keep the shape valid
+    pub fn is_valid(&self) -> bool {
+        match self.shape {
+            Shape::Polygon => self.points.is_empty() || is_simple(&self.outline(), false),
+            Shape::Rectangle | Shape::Circle => {
+                self.points.is_empty() || is_valid_polygon(&self.outline())
+            }
+        }
+    }
+
+    /// Place a point, returning the vertices of the shape when it is finished. Clicks that
+    /// would make the shape cross itself are ignored.
+    pub fn click(&mut self, pos: Point2<f32>) -> Option<Vec<Point2<f32>>> {
+        self.cursor = pos;
+
+        match self.shape {
+            Shape::Polygon => {
+                // Clicking the first or the last vertex again closes the polygon, so a
+                // double-click finishes it
+                let closes = |vertex: Option<&Point2<f32>>| {
+                    vertex.is_some_and(|v| distance(v, &pos) < CLOSE_DISTANCE)
+                };
+                if closes(self.points.first()) || closes(self.points.last()) {
+                    return self.finish();
+                }
+                if self.is_valid() {
+                    self.points.push(pos);
+                }
+                None
+            }
+            Shape::Rectangle | Shape::Circle => {
+                if self.points.is_empty() {
+                    self.points.push(pos);
+                    return None;
+                }
+                self.finish()
+            }
+        }
+    }
+
+    /// The vertices of the finished shape, if it is a valid polygon
+    pub fn finish(&self) -> Option<Vec<Point2<f32>>> {
+        let verts = match self.shape {
+            Shape::Polygon => self.points.clone(),
+            Shape::Rectangle | Shape::Circle => self.outline(),
+        };
+        is_valid_polygon(&verts).then_some(verts)
+    }
+
+    /// Undo the last click, returning false if there was nothing to undo
+    pub fn remove_last_point(&mut self) -> bool {
+        self.points.pop().is_some()
+    }
+}
+
+/// Whether the vertices make a closed polygon with an area that does not cross itself
+pub fn is_valid_polygon(verts: &[Point2<f32>]) -> bool {
+    verts.len() >= 3 && area(verts).abs() > EPSILON && is_simple(verts, true)
+}
+
+/// Whether no edge of the path crosses or touches an edge that it is not connected to, and no
+/// edge doubles back over the one before it
+fn is_simple(verts: &[Point2<f32>], closed: bool) -> bool {
+    let n = verts.len();
+    let edge_count = if closed { n } else { n.saturating_sub(1) };
+    let edge = |i: usize| (verts[i], verts[(i + 1) % n]);
+
+    for i in 0..edge_count {
+        let (a, b) = edge(i);
+        if distance(&a, &b) < EPSILON {
+            return false;
+        }
+
+        for j in (i + 1)..edge_count {
+            let (c, d) = edge(j);
+            let follows = j == i + 1;
+            let wraps = closed && i == 0 && j == n - 1;
+            let crosses = if follows {
+                doubles_back(a, b, d)
+            } else if wraps {
+                doubles_back(c, d, b)
+            } else {
+                segments_touch(a, b, c, d)
+            };
+            if crosses {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+fn cross(o: Point2<f32>, a: Point2<f32>, b: Point2<f32>) -> f32 {
+    (a - o).perp(&(b - o))
+}
+
+/// Whether the edge from `b` to `c` goes back along the edge from `a` to `b`
+fn doubles_back(a: Point2<f32>, b: Point2<f32>, c: Point2<f32>) -> bool {
+    cross(a, b, c).abs() < EPSILON && (b - a).dot(&(c - b)) < 0.0
+}
+
+/// Whether `p` lies on the segment from `a` to `b`, given that the three points are collinear
+fn within(a: Point2<f32>, b: Point2<f32>, p: Point2<f32>) -> bool {
+    p.x >= a.x.min(b.x) - EPSILON
+        && p.x <= a.x.max(b.x) + EPSILON
+        && p.y >= a.y.min(b.y) - EPSILON
+        && p.y <= a.y.max(b.y) + EPSILON
+}
+
+/// Whether the segments cross or touch, including at their ends
+fn segments_touch(a: Point2<f32>, b: Point2<f32>, c: Point2<f32>, d: Point2<f32>) -> bool {
+    let sign = |value: f32| {
+        if value.abs() < EPSILON {
+            0
+        } else {
+            value.signum() as i32
+        }
+    };
+    let d1 = sign(cross(a, b, c));
+    let d2 = sign(cross(a, b, d));
+    let d3 = sign(cross(c, d, a));
+    let d4 = sign(cross(c, d, b));
+
+    if d1 * d2 < 0 && d3 * d4 < 0 {
+        return true;
+    }
+
+    (d1 == 0 && within(a, b, c))
+        || (d2 == 0 && within(a, b, d))
+        || (d3 == 0 && within(c, d, a))
+        || (d4 == 0 && within(c, d, b))
+}
+
+/// Signed area of the polygon, using the shoelace formula
+fn area(verts: &[Point2<f32>]) -> f32 {
+    let n = verts.len();
+    (0..n)
+        .map(|i| verts[i].coords.perp(&verts[(i + 1) % n].coords))
+        .sum::<f32>()
+        / 2.0
+}
+
+#[cfg(test)]
+mod draw_tool_tests {
+    use super::{is_valid_polygon, DrawTarget, Shape, ShapeDrawing};
+    use nalgebra::Point2;
+
+    fn drawing(shape: Shape) -> ShapeDrawing {
+        ShapeDrawing::new(DrawTarget::Obstacle, shape, 8, Point2::new(0.0, 0.0))
+    }
+
+    #[test]
+    fn polygon_closes_on_double_click() {
+        let mut drawing = drawing(Shape::Polygon);
+        assert_eq!(drawing.click(Point2::new(0.0, 0.0)), None);
+        assert_eq!(drawing.click(Point2::new(100.0, 0.0)), None);
+        assert_eq!(drawing.click(Point2::new(100.0, 100.0)), None);
+        let verts = drawing.click(Point2::new(100.0, 100.0)).unwrap();
+        assert_eq!(verts.len(), 3);
+    }
+
+    #[test]
+    fn crossing_clicks_are_ignored() {
+        let mut drawing = drawing(Shape::Polygon);
+        for (x, y) in [(0.0, 0.0), (100.0, 0.0), (100.0, 100.0)] {
+            drawing.click(Point2::new(x, y));
+        }
+        // The edge to this point would cross the first edge
+        drawing.click(Point2::new(50.0, -50.0));
+        assert_eq!(drawing.points().len(), 3);
+        assert!(!drawing.is_valid());
+
+        drawing.click(Point2::new(0.0, 100.0));
+        assert_eq!(drawing.points().len(), 4);
+        assert_eq!(drawing.finish().map(|verts| verts.len()), Some(4));
+    }
+
+    #[test]
+    fn rectangles_and_circles_need_an_area() {
+        let mut rectangle = drawing(Shape::Rectangle);
+        rectangle.click(Point2::new(0.0, 0.0));
+        assert_eq!(rectangle.click(Point2::new(100.0, 0.0)), None);
+        let verts = rectangle.click(Point2::new(100.0, 50.0)).unwrap();
+        assert_eq!(verts[2], Point2::new(100.0, 50.0));
+
+        let mut circle = drawing(Shape::Circle);
+        circle.click(Point2::new(0.0, 0.0));
+        assert_eq!(circle.click(Point2::new(0.0, 0.0)), None);
+        assert_eq!(circle.click(Point2::new(50.0, 0.0)).unwrap().len(), 8);
+    }
+
+    #[test]
+    fn bow_tie_is_not_valid() {
+        let square = [
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(0.0, 1.0),
+        ];
+        assert!(is_valid_polygon(&square));
+        let bow_tie = [square[0], square[1], square[3], square[2]];
+        assert!(!is_valid_polygon(&bow_tie));
+        let line = [square[0], square[1], Point2::new(2.0, 0.0)];
+        assert!(!is_valid_polygon(&line));
+    }
+}
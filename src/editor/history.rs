@@ -0,0 +1,94 @@
+use crate::game::{level_info::LevelInfo, Game};
+
+/// How many changes can be undone
+const MAX_UNDO: usize = 100;
+
+/// Snapshots of the level in the level format, for undoing and redoing changes
+pub struct History {
+    /// The level as it was after the last change
+    current: String,
+    undo: Vec<String>,
+    redo: Vec<String>,
+}
+
+fn snapshot(game: &Game) -> String {
+    serde_json::to_string(&game.to_level_info()).unwrap_or_default()
+}
+
+impl History {
+    pub fn new(game: &Game) -> Self {
+        History {
+            current: snapshot(game),
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Remember the level if it changed since the last commit, returning whether it did
+    pub fn commit(&mut self, game: &Game) -> bool {
+        let snapshot = snapshot(game);
+        if snapshot == self.current {
+            return false;
+        }
+
+        self.undo
+            .push(std::mem::replace(&mut self.current, snapshot));
+        if self.undo.len() > MAX_UNDO {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+        true
+    }
+
+    /// The level before the last change
+    pub fn undo(&mut self) -> Option<LevelInfo> {
+        let previous = self.undo.pop()?;
+        self.redo
+            .push(std::mem::replace(&mut self.current, previous));
+        serde_json::from_str(&self.current).ok()
+    }
+
+    /// The level after the last undone change
+    pub fn redo(&mut self) -> Option<LevelInfo> {
+        let next = self.redo.pop()?;
+        self.undo.push(std::mem::replace(&mut self.current, next));
+        serde_json::from_str(&self.current).ok()
+    }
+
+    /// Take the restored level as the current one, since loading a level can round some of its
+    /// values
+    pub fn restored(&mut self, game: &Game) {
+        self.current = snapshot(game);
+    }
+}
+
+#[cfg(test)]
+mod history_tests {
+    use super::History;
+    use crate::game::Game;
+    use nalgebra::Point2;
+
+    #[test]
+    fn undo_and_redo_changes() {
+        let mut game = Game::new();
+        let mut history = History::new(&game);
+        assert!(!history.commit(&game));
+
+        game.actors[0].pos = Point2::new(10.0, 20.0);
+        assert!(history.commit(&game));
+        game.actors[0].pos = Point2::new(30.0, 20.0);
+        assert!(history.commit(&game));
+
+        let level_info = history.undo().unwrap();
+        assert_eq!(level_info.player_data, (10.0, 20.0));
+        let level_info = history.redo().unwrap();
+        assert_eq!(level_info.player_data, (30.0, 20.0));
+        assert!(history.redo().is_none());
+
+        // A new change after undoing can not be redone past
+        history.undo();
+        game.actors[0].pos = Point2::new(0.0, 0.0);
+        assert!(history.commit(&game));
+        assert!(history.redo().is_none());
+    }
+}
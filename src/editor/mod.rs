@@ -1,24 +1,56 @@
-use crate::game::{
-    actor::Actor, controller::Controller, game_map::GameMap, polygon::Polygon, Game,
+pub mod copy_paste;
+pub mod draw_tool;
+pub mod history;
+pub mod snapping;
+
+use crate::{
+    editor::snapping::SnapSource,
+    game::{actor::Actor, controller::Controller, game_map::GameMap, polygon::Polygon, Game},
+    view::editor::snap_to_grid,
 };
-use ggez::event::MouseButton;
-use nalgebra::{distance, Point2};
-use std::cmp::Ordering::Equal;
+use ggez::{event::MouseButton, graphics::Rect};
+use nalgebra::{distance, Point2, Rotation2, Vector2};
+use std::cmp::{Ordering::Equal, Reverse};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PolygonType {
-    EndArea,
+    EndArea { index: usize },
     Obstacle { index: usize },
+    LightZone { index: usize },
 }
 
 impl PolygonType {
+    fn index(self) -> usize {
+        match self {
+            Self::EndArea { index } | Self::Obstacle { index } | Self::LightZone { index } => index,
+        }
+    }
+
+    pub fn get(self, game_map: &GameMap) -> &Polygon {
+        match self {
+            Self::EndArea { index } => &game_map.end_areas[index],
+            Self::Obstacle { index } => &game_map.obstacles[index],
+            Self::LightZone { index } => &game_map.light_zones[index].polygon,
+        }
+    }
+
     fn find(self, game_map: &mut GameMap) -> &mut Polygon {
         match self {
-            Self::EndArea => &mut game_map.end_area,
+            Self::EndArea { index } => game_map
+                .end_areas
+                .get_mut(index)
+                .unwrap_or_else(|| panic!("Could not find end area at index {}", index)),
             Self::Obstacle { index } => game_map
                 .obstacles
                 .get_mut(index)
                 .unwrap_or_else(|| panic!("Could not find obstacle at index {}", index)),
+            Self::LightZone { index } => {
+                &mut game_map
+                    .light_zones
+                    .get_mut(index)
+                    .unwrap_or_else(|| panic!("Could not find light zone at index {}", index))
+                    .polygon
+            }
         }
     }
 }
@@ -38,76 +70,304 @@ pub enum DragObject {
         polygon_type: PolygonType,
         index: usize,
     },
+    /// Everything that is selected, moved as far as the mouse moves
+    Selection {
+        last_pos: Option<Point2<f32>>,
+    },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SelectionObject {
-    Actor { index: usize },
-    Polygon { polygon_type: PolygonType },
+    Actor {
+        index: usize,
+    },
+    Polygon {
+        polygon_type: PolygonType,
+    },
+    GuardPathVertex {
+        actor_index: usize,
+        vertex_index: usize,
+    },
+    PolygonVertex {
+        polygon_type: PolygonType,
+        index: usize,
+    },
+}
+
+impl SelectionObject {
+    fn is_vertex(&self) -> bool {
+        matches!(
+            self,
+            SelectionObject::GuardPathVertex { .. } | SelectionObject::PolygonVertex { .. }
+        )
+    }
+}
+
+/// A single point moved by group transforms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PointRef {
+    Actor(usize),
+    GuardPath(usize, usize),
+    Polygon(PolygonType, usize),
+}
+
+/// A change applied to every selected point at once, around the center of the selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    RotateLeft,
+    RotateRight,
+    ScaleUp,
+    ScaleDown,
+    MirrorHorizontal,
+    MirrorVertical,
+}
+
+impl Transform {
+    /// Rotations are limited to quarter turns while snapping, so shapes stay on the grid
+    pub fn apply(self, point: Point2<f32>, pivot: Point2<f32>, snap: bool) -> Point2<f32> {
+        let step = if snap { 90.0 } else { 15.0 };
+        let scale = if snap { 2.0 } else { 1.25 };
+        let offset = point - pivot;
+
+        let offset = match self {
+            Transform::RotateLeft => Rotation2::new(-f32::to_radians(step)) * offset,
+            Transform::RotateRight => Rotation2::new(f32::to_radians(step)) * offset,
+            Transform::ScaleUp => offset * scale,
+            Transform::ScaleDown => offset / scale,
+            Transform::MirrorHorizontal => Vector2::new(-offset.x, offset.y),
+            Transform::MirrorVertical => Vector2::new(offset.x, -offset.y),
+        };
+        pivot + offset
+    }
 }
 
 pub struct SelectionHandler {
     dragged_object: Option<DragObject>,
-    pub selected_object: Option<SelectionObject>,
+    pub selection: Vec<SelectionObject>,
+    /// The corner where a box selection started and the corner at the mouse
+    pub selection_box: Option<(Point2<f32>, Point2<f32>)>,
+    /// Whether the box selection adds to the selection instead of replacing it
+    box_adds: bool,
 }
 
 impl SelectionHandler {
     pub fn new() -> Self {
         SelectionHandler {
             dragged_object: None,
-            selected_object: None,
+            selection: Vec::new(),
+            selection_box: None,
+            box_adds: false,
+        }
+    }
+
+    pub fn is_selected(&self, object: SelectionObject) -> bool {
+        self.selection.contains(&object)
+    }
+
+    /// The selected object if exactly one is selected
+    pub fn single(&self) -> Option<SelectionObject> {
+        match self.selection.as_slice() {
+            [object] => Some(*object),
+            _ => None,
+        }
+    }
+
+    /// Whether the vertices of a polygon can be dragged, because it or one of them is selected
+    pub fn shows_vertices(&self, polygon_type: PolygonType) -> bool {
+        self.selection.iter().any(|object| match *object {
+            SelectionObject::Polygon { polygon_type: p } => p == polygon_type,
+            SelectionObject::PolygonVertex {
+                polygon_type: p, ..
+            } => p == polygon_type,
+            _ => false,
+        })
+    }
+
+    /// Whether the patrol path of a guard can be edited, because it or one of its waypoints
+    /// is selected
+    pub fn shows_patrol_path(&self, actor_index: usize) -> bool {
+        self.selection.iter().any(|object| match *object {
+            SelectionObject::Actor { index } => index == actor_index,
+            SelectionObject::GuardPathVertex { actor_index: i, .. } => i == actor_index,
+            _ => false,
+        })
+    }
+
+    /// Whether the mouse is held down to drag objects or a selection box
+    pub fn is_dragging(&self) -> bool {
+        self.dragged_object.is_some() || self.selection_box.is_some()
+    }
+
+    /// What the dragged vertex or waypoint belongs to and the point before it, if a single point
+    /// is dragged that can be snapped to other objects
+    pub fn dragged_point(&self, game: &Game) -> Option<(SnapSource, Option<Point2<f32>>)> {
+        let previous = |verts: &[Point2<f32>], index: usize| {
+            let count = verts.len();
+            (count > 1).then(|| verts[(index + count - 1) % count])
+        };
+
+        match *self.dragged_object.as_ref()? {
+            DragObject::PolygonVertex {
+                polygon_type,
+                index,
+            } => {
+                let verts = &polygon_type.get(&game.game_map).verts;
+                Some((SnapSource::Polygon(polygon_type), previous(verts, index)))
+            }
+            DragObject::GuardPathVertex {
+                actor_index,
+                vertex_index,
+            } => match &game.actors.get(actor_index)?.controller {
+                Controller::Guard(guard) => Some((
+                    SnapSource::GuardPath(actor_index),
+                    previous(&guard.points.verts, vertex_index),
+                )),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Add the object to the selection, or remove it if it is already selected
+    fn toggle(&mut self, object: SelectionObject) {
+        match self.selection.iter().position(|o| *o == object) {
+            Some(i) => {
+                self.selection.remove(i);
+            }
+            None => self.selection.push(object),
         }
     }
 
+    /// Press the mouse on the level, where `add` adds to the selection instead of replacing it
     pub fn handle_mouse_down(
         &mut self,
         game: &mut Game,
         button: MouseButton,
         mouse_pos: Point2<f32>,
+        add: bool,
     ) {
         match button {
-            MouseButton::Left => self.dragged_object = self.find_object_to_drag(game, mouse_pos),
+            MouseButton::Left if add => match self.find_object_at(game, mouse_pos) {
+                Some(object) => self.toggle(object),
+                None => self.start_box(mouse_pos, true),
+            },
+            MouseButton::Left => {
+                // Pressing on something that is selected together with other things moves them all
+                let object = self.find_object_at(game, mouse_pos);
+                if self.selection.len() > 1 && object.is_some_and(|o| self.is_selected(o)) {
+                    self.dragged_object = Some(DragObject::Selection { last_pos: None });
+                    return;
+                }
+
+                self.dragged_object = self.find_object_to_drag(game, mouse_pos);
+                if self.dragged_object.is_none() {
+                    self.start_box(mouse_pos, false);
+                }
+            }
             MouseButton::Right => self.delete_object(game, mouse_pos),
             _ => {}
         }
     }
 
+    fn start_box(&mut self, mouse_pos: Point2<f32>, add: bool) {
+        self.selection_box = Some((mouse_pos, mouse_pos));
+        self.box_adds = add;
+    }
+
+    /// The vertex or object under the mouse, preferring vertices of selected objects
+    fn find_object_at(&self, game: &mut Game, mouse_pos: Point2<f32>) -> Option<SelectionObject> {
+        for object in self.selection.clone() {
+            match object {
+                SelectionObject::Polygon { polygon_type }
+                | SelectionObject::PolygonVertex { polygon_type, .. } => {
+                    let polygon = polygon_type.find(&mut game.game_map);
+                    if let Some(index) = self.find_polygon_vertex_at(polygon, mouse_pos) {
+                        return Some(SelectionObject::PolygonVertex {
+                            polygon_type,
+                            index,
+                        });
+                    }
+                }
+                SelectionObject::Actor { index: actor_index }
+                | SelectionObject::GuardPathVertex { actor_index, .. } => {
+                    if let Some(Controller::Guard(guard)) =
+                        game.actors.get(actor_index).map(|actor| &actor.controller)
+                    {
+                        if let Some(vertex_index) =
+                            self.find_polygon_vertex_at(&guard.points, mouse_pos)
+                        {
+                            return Some(SelectionObject::GuardPathVertex {
+                                actor_index,
+                                vertex_index,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(index) = self.find_actor_at(game, mouse_pos) {
+            return Some(SelectionObject::Actor { index });
+        }
+
+        let polygon_type = if let Some(index) = self.find_end_area_at(game, mouse_pos) {
+            PolygonType::EndArea { index }
+        } else if let Some(index) = self.find_polygon_at(game, mouse_pos) {
+            PolygonType::Obstacle { index }
+        } else {
+            PolygonType::LightZone {
+                index: self.find_light_zone_at(game, mouse_pos)?,
+            }
+        };
+        Some(SelectionObject::Polygon { polygon_type })
+    }
+
     fn find_object_to_drag(&self, game: &mut Game, mouse_pos: Point2<f32>) -> Option<DragObject> {
         // Check if there is a draggable vertex under the mouse
-        if let Some(SelectionObject::Polygon { polygon_type }) = self.selected_object {
-            let polygon = polygon_type.find(&mut game.game_map);
-            if let Some(i) = self.find_polygon_vertex_at(polygon, mouse_pos) {
-                return Some(DragObject::PolygonVertex {
-                    polygon_type,
-                    index: i,
-                });
-            } else if let Some(i) = self.find_polygon_pseudo_vertex_at(polygon, mouse_pos) {
-                // Add and drag
-                polygon.verts.insert(i + 1, mouse_pos);
-                return Some(DragObject::PolygonVertex {
-                    polygon_type,
-                    index: i + 1,
-                });
-            }
-        } else if let Some(SelectionObject::Actor { index }) = self.selected_object {
-            let actor = game.actors.get_mut(index);
-            if let Some(Actor {
-                controller: Controller::Guard(guard),
-                ..
-            }) = actor
-            {
-                if let Some(i) = self.find_polygon_vertex_at(&guard.points, mouse_pos) {
-                    return Some(DragObject::GuardPathVertex {
-                        actor_index: index,
-                        vertex_index: i,
-                    });
-                } else if let Some(i) = self.find_polygon_pseudo_vertex_at(&guard.points, mouse_pos)
-                {
-                    guard.points.verts.insert(i + 1, mouse_pos);
-                    return Some(DragObject::GuardPathVertex {
-                        actor_index: index,
-                        vertex_index: i + 1,
-                    });
+        for object in self.selection.clone() {
+            match object {
+                SelectionObject::Polygon { polygon_type }
+                | SelectionObject::PolygonVertex { polygon_type, .. } => {
+                    let polygon = polygon_type.find(&mut game.game_map);
+                    if let Some(i) = self.find_polygon_vertex_at(polygon, mouse_pos) {
+                        return Some(DragObject::PolygonVertex {
+                            polygon_type,
+                            index: i,
+                        });
+                    } else if let Some(i) = self.find_polygon_pseudo_vertex_at(polygon, mouse_pos) {
+                        // Add and drag
+                        polygon.verts.insert(i + 1, mouse_pos);
+                        return Some(DragObject::PolygonVertex {
+                            polygon_type,
+                            index: i + 1,
+                        });
+                    }
+                }
+                SelectionObject::Actor { index }
+                | SelectionObject::GuardPathVertex {
+                    actor_index: index, ..
+                } => {
+                    let actor = game.actors.get_mut(index);
+                    if let Some(Actor {
+                        controller: Controller::Guard(guard),
+                        ..
+                    }) = actor
+                    {
+                        if let Some(i) = self.find_polygon_vertex_at(&guard.points, mouse_pos) {
+                            return Some(DragObject::GuardPathVertex {
+                                actor_index: index,
+                                vertex_index: i,
+                            });
+                        } else if let Some(i) =
+                            self.find_polygon_pseudo_vertex_at(&guard.points, mouse_pos)
+                        {
+                            guard.insert_waypoint(i + 1, mouse_pos);
+                            return Some(DragObject::GuardPathVertex {
+                                actor_index: index,
+                                vertex_index: i + 1,
+                            });
+                        }
+                    }
                 }
             }
         }
@@ -116,9 +376,9 @@ impl SelectionHandler {
             return Some(DragObject::Actor { index: i });
         }
 
-        if self.has_end_area_at(game, mouse_pos) {
+        if let Some(i) = self.find_end_area_at(game, mouse_pos) {
             return Some(DragObject::Polygon {
-                polygon_type: PolygonType::EndArea,
+                polygon_type: PolygonType::EndArea { index: i },
             });
         }
 
@@ -128,26 +388,46 @@ impl SelectionHandler {
             });
         }
 
+        if let Some(i) = self.find_light_zone_at(game, mouse_pos) {
+            return Some(DragObject::Polygon {
+                polygon_type: PolygonType::LightZone { index: i },
+            });
+        }
+
         None
     }
 
     fn delete_object(&mut self, game: &mut Game, mouse_pos: Point2<f32>) {
-        if let Some(SelectionObject::Polygon { polygon_type }) = self.selected_object {
-            let polygon = polygon_type.find(&mut game.game_map);
+        // Selected vertices of the changed shape would point at the wrong vertices
+        for object in self.selection.clone() {
+            match object {
+                SelectionObject::Polygon { polygon_type }
+                | SelectionObject::PolygonVertex { polygon_type, .. } => {
+                    let polygon = polygon_type.find(&mut game.game_map);
 
-            if polygon.verts.len() > 3 {
-                if let Some(i) = self.find_polygon_vertex_at(polygon, mouse_pos) {
-                    polygon.verts.remove(i);
-                    return;
+                    if polygon.verts.len() > 3 {
+                        if let Some(i) = self.find_polygon_vertex_at(polygon, mouse_pos) {
+                            polygon.verts.remove(i);
+                            self.selection.retain(|o| !o.is_vertex());
+                            return;
+                        }
+                    }
                 }
-            }
-        }
-        if let Some(SelectionObject::Actor { index }) = self.selected_object {
-            if let Some(actor) = game.actors.get_mut(index) {
-                if let Controller::Guard(guard) = &mut actor.controller {
-                    if guard.points.verts.len() > 3 {
-                        if let Some(i) = self.find_polygon_vertex_at(&guard.points, mouse_pos) {
-                            guard.points.verts.remove(i);
+                SelectionObject::Actor { index }
+                | SelectionObject::GuardPathVertex {
+                    actor_index: index, ..
+                } => {
+                    if let Some(actor) = game.actors.get_mut(index) {
+                        if let Controller::Guard(guard) = &mut actor.controller {
+                            if guard.points.verts.len() > 3 {
+                                if let Some(i) =
+                                    self.find_polygon_vertex_at(&guard.points, mouse_pos)
+                                {
+                                    guard.remove_waypoint(i);
+                                    self.selection.retain(|o| !o.is_vertex());
+                                    return;
+                                }
+                            }
                         }
                     }
                 }
@@ -190,8 +470,11 @@ impl SelectionHandler {
             .map(|(i, _)| i)
     }
 
-    fn has_end_area_at(&self, game: &mut Game, mouse_pos: Point2<f32>) -> bool {
-        game.game_map.end_area.contains(mouse_pos)
+    fn find_end_area_at(&self, game: &mut Game, mouse_pos: Point2<f32>) -> Option<usize> {
+        game.game_map
+            .end_areas
+            .iter()
+            .position(|end_area| end_area.contains(mouse_pos))
     }
 
     fn find_polygon_at(&self, game: &mut Game, mouse_pos: Point2<f32>) -> Option<usize> {
@@ -203,70 +486,99 @@ impl SelectionHandler {
             .map(|(i, _)| i)
     }
 
+    fn find_light_zone_at(&self, game: &mut Game, mouse_pos: Point2<f32>) -> Option<usize> {
+        game.game_map
+            .light_zones
+            .iter()
+            .enumerate()
+            .find(|(_, zone)| zone.polygon.contains(mouse_pos))
+            .map(|(i, _)| i)
+    }
+
     pub fn handle_mouse_motion(&mut self, game: &mut Game, mouse_pos: Point2<f32>) {
-        match &self.dragged_object {
-            Some(object) => {
-                match object {
-                    DragObject::Actor { index } => {
-                        let selected = game
-                            .actors
-                            .get_mut(*index)
-                            .expect("could not find selected element");
-                        selected.pos = mouse_pos;
-                    }
-                    &DragObject::GuardPathVertex {
-                        actor_index,
-                        vertex_index,
-                    } => {
-                        if let Some(actor) = game.actors.get_mut(actor_index) {
-                            if let Controller::Guard(guard) = &mut actor.controller {
-                                if let Some(vertex) = guard.points.verts.get_mut(vertex_index) {
-                                    *vertex = mouse_pos
-                                }
+        if let Some((_, corner)) = &mut self.selection_box {
+            *corner = mouse_pos;
+            return;
+        }
+
+        if let Some(DragObject::Selection { last_pos }) = self.dragged_object {
+            // The mouse position is snapped, so the selection moves in whole grid steps
+            let delta = mouse_pos - last_pos.unwrap_or(mouse_pos);
+            for point in self.selected_points(game) {
+                if let Some(point) = point_mut(game, point) {
+                    *point += delta;
+                }
+            }
+            self.dragged_object = Some(DragObject::Selection {
+                last_pos: Some(mouse_pos),
+            });
+            return;
+        }
+
+        if let Some(object) = &self.dragged_object {
+            match object {
+                DragObject::Actor { index } => {
+                    let selected = game
+                        .actors
+                        .get_mut(*index)
+                        .expect("could not find selected element");
+                    selected.pos = mouse_pos;
+                }
+                &DragObject::GuardPathVertex {
+                    actor_index,
+                    vertex_index,
+                } => {
+                    if let Some(actor) = game.actors.get_mut(actor_index) {
+                        if let Controller::Guard(guard) = &mut actor.controller {
+                            if let Some(vertex) = guard.points.verts.get_mut(vertex_index) {
+                                *vertex = mouse_pos
                             }
                         }
                     }
-                    DragObject::Polygon { polygon_type } => {
-                        let polygon = polygon_type.find(&mut game.game_map);
-
-                        // Get centroid of polygon (avg of points)
-                        let num_points = polygon.verts.len() as f32;
-                        let (acc_x, acc_y) = polygon
-                            .verts
-                            .iter()
-                            .map(|v| (v.x, v.y))
-                            .reduce(|(x1, y1), (x2, y2)| (x1 + x2, y1 + y2))
-                            .expect("no verts found");
-                        let centroid = Point2::new(acc_x / num_points, acc_y / num_points);
-
-                        let mouse_delta = mouse_pos - centroid;
-                        for vertex in polygon_type.find(&mut game.game_map).verts.iter_mut() {
-                            *vertex += mouse_delta;
-                        }
+                }
+                DragObject::Polygon { polygon_type } => {
+                    let polygon = polygon_type.find(&mut game.game_map);
+
+                    // Get centroid of polygon (avg of points)
+                    let num_points = polygon.verts.len() as f32;
+                    let (acc_x, acc_y) = polygon
+                        .verts
+                        .iter()
+                        .map(|v| (v.x, v.y))
+                        .reduce(|(x1, y1), (x2, y2)| (x1 + x2, y1 + y2))
+                        .expect("no verts found");
+                    let centroid = Point2::new(acc_x / num_points, acc_y / num_points);
+
+                    let mouse_delta = mouse_pos - centroid;
+                    for vertex in polygon_type.find(&mut game.game_map).verts.iter_mut() {
+                        *vertex += mouse_delta;
                     }
-                    DragObject::PolygonVertex {
-                        polygon_type,
-                        index,
-                    } => {
-                        let polygon = polygon_type.find(&mut game.game_map);
-                        if let Some(vertex) = polygon.verts.get_mut(*index) {
-                            *vertex = mouse_pos
-                        }
+                }
+                DragObject::PolygonVertex {
+                    polygon_type,
+                    index,
+                } => {
+                    let polygon = polygon_type.find(&mut game.game_map);
+                    if let Some(vertex) = polygon.verts.get_mut(*index) {
+                        *vertex = mouse_pos
                     }
                 }
+                DragObject::Selection { .. } => {}
             }
-            None => {}
         }
     }
 
-    pub fn handle_mouse_up(&mut self, _game: &mut Game, button: MouseButton) {
+    pub fn handle_mouse_up(&mut self, game: &mut Game, button: MouseButton) {
         if button == MouseButton::Left {
-            self.end_drag()
+            match self.selection_box.take() {
+                Some((start, end)) => self.select_in_box(game, start, end),
+                None => self.end_drag(),
+            }
         }
     }
 
     fn end_drag(&mut self) {
-        self.selected_object = match self.dragged_object {
+        let selected = match self.dragged_object.take() {
             Some(DragObject::Actor { index }) => Some(SelectionObject::Actor { index }),
             Some(DragObject::GuardPathVertex { actor_index, .. }) => {
                 Some(SelectionObject::Actor { index: actor_index })
@@ -277,9 +589,424 @@ impl SelectionHandler {
             Some(DragObject::PolygonVertex { polygon_type, .. }) => {
                 Some(SelectionObject::Polygon { polygon_type })
             }
+            // Moving the selection keeps it selected
+            Some(DragObject::Selection { .. }) => return,
             None => None,
         };
 
-        self.dragged_object = None;
+        self.selection = selected.into_iter().collect();
+    }
+
+    /// Select everything inside the box, and the vertices inside it of objects whose vertices
+    /// were shown
+    fn select_in_box(&mut self, game: &mut Game, start: Point2<f32>, end: Point2<f32>) {
+        let bounds = Rect::new(
+            start.x.min(end.x),
+            start.y.min(end.y),
+            (end.x - start.x).abs(),
+            (end.y - start.y).abs(),
+        );
+        let inside = |verts: &[Point2<f32>]| verts.iter().all(|v| bounds.contains(*v));
+
+        let mut objects = Vec::new();
+        for (index, actor) in game.actors.iter().enumerate() {
+            if bounds.contains(actor.pos) {
+                objects.push(SelectionObject::Actor { index });
+            } else if let Controller::Guard(guard) = &actor.controller {
+                if self.shows_patrol_path(index) {
+                    for (vertex_index, vertex) in guard.points.verts.iter().enumerate() {
+                        if bounds.contains(*vertex) {
+                            objects.push(SelectionObject::GuardPathVertex {
+                                actor_index: index,
+                                vertex_index,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        let game_map = &game.game_map;
+        let polygons = (game_map.obstacles.iter().enumerate())
+            .map(|(index, p)| (PolygonType::Obstacle { index }, p))
+            .chain(
+                (game_map.end_areas.iter().enumerate())
+                    .map(|(index, p)| (PolygonType::EndArea { index }, p)),
+            )
+            .chain(
+                (game_map.light_zones.iter().enumerate())
+                    .map(|(index, zone)| (PolygonType::LightZone { index }, &zone.polygon)),
+            );
+        for (polygon_type, polygon) in polygons {
+            if inside(&polygon.verts) {
+                objects.push(SelectionObject::Polygon { polygon_type });
+            } else if self.shows_vertices(polygon_type) {
+                for (index, vertex) in polygon.verts.iter().enumerate() {
+                    if bounds.contains(*vertex) {
+                        objects.push(SelectionObject::PolygonVertex {
+                            polygon_type,
+                            index,
+                        });
+                    }
+                }
+            }
+        }
+
+        if !self.box_adds {
+            self.selection.clear();
+        }
+        for object in objects {
+            if !self.is_selected(object) {
+                self.selection.push(object);
+            }
+        }
+    }
+
+    /// Every point that is moved by group transforms, each only once
+    fn selected_points(&self, game: &Game) -> Vec<PointRef> {
+        let mut points = Vec::new();
+        for object in &self.selection {
+            match *object {
+                SelectionObject::Actor { index } => {
+                    points.push(PointRef::Actor(index));
+                    if let Some(Controller::Guard(guard)) =
+                        game.actors.get(index).map(|actor| &actor.controller)
+                    {
+                        for i in 0..guard.points.verts.len() {
+                            points.push(PointRef::GuardPath(index, i));
+                        }
+                    }
+                }
+                SelectionObject::Polygon { polygon_type } => {
+                    for i in 0..polygon_type.get(&game.game_map).verts.len() {
+                        points.push(PointRef::Polygon(polygon_type, i));
+                    }
+                }
+                SelectionObject::GuardPathVertex {
+                    actor_index,
+                    vertex_index,
+                } => points.push(PointRef::GuardPath(actor_index, vertex_index)),
+                SelectionObject::PolygonVertex {
+                    polygon_type,
+                    index,
+                } => points.push(PointRef::Polygon(polygon_type, index)),
+            }
+        }
+
+        let mut unique = Vec::new();
+        for point in points {
+            if !unique.contains(&point) {
+                unique.push(point);
+            }
+        }
+        unique
+    }
+
+    /// The points that make up the position of a single selected object, where an actor or
+    /// vertex is a single point and a shape is all of its vertices
+    fn inspected_points(&self, game: &Game) -> Vec<PointRef> {
+        match self.single() {
+            Some(SelectionObject::Actor { index }) => vec![PointRef::Actor(index)],
+            Some(_) => self.selected_points(game),
+            None => Vec::new(),
+        }
+    }
+
+    /// The position of the selected object, the center of it if it is a shape
+    pub fn position(&self, game: &Game) -> Option<Point2<f32>> {
+        let positions: Vec<Point2<f32>> = self
+            .inspected_points(game)
+            .into_iter()
+            .filter_map(|point| point_ref(game, point))
+            .collect();
+        center(&positions)
+    }
+
+    /// Move the selected object so its position is `pos`
+    pub fn set_position(&self, game: &mut Game, pos: Point2<f32>) {
+        let Some(delta) = self.position(game).map(|old| pos - old) else {
+            return;
+        };
+        for point in self.inspected_points(game) {
+            if let Some(point) = point_mut(game, point) {
+                *point += delta;
+            }
+        }
+    }
+
+    /// Apply the transform around the center of the selection, snapping the moved points to
+    /// the grid if `snap` is set
+    pub fn transform_selection(&self, game: &mut Game, transform: Transform, snap: bool) {
+        let points = self.selected_points(game);
+        let positions: Vec<Point2<f32>> = points
+            .iter()
+            .filter_map(|point| point_mut(game, *point).map(|p| *p))
+            .collect();
+        let Some(pivot) = center(&positions) else {
+            return;
+        };
+        let pivot = if snap { snap_to_grid(pivot) } else { pivot };
+
+        for point in points {
+            if let Some(point) = point_mut(game, point) {
+                let moved = transform.apply(*point, pivot, snap);
+                *point = if snap { snap_to_grid(moved) } else { moved };
+            }
+        }
+    }
+
+    /// Delete the selected objects and vertices, keeping the player, one end area and at least
+    /// three vertices in every shape
+    pub fn delete_selection(&mut self, game: &mut Game) {
+        let selection = std::mem::take(&mut self.selection);
+
+        // Remove vertices first, while the indices of the shapes they belong to are still valid
+        let mut vertices: Vec<SelectionObject> = selection
+            .iter()
+            .copied()
+            .filter(|object| object.is_vertex())
+            .collect();
+        vertices.sort_by_key(|object| match *object {
+            SelectionObject::GuardPathVertex { vertex_index, .. } => Reverse(vertex_index),
+            SelectionObject::PolygonVertex { index, .. } => Reverse(index),
+            _ => Reverse(0),
+        });
+        for object in vertices {
+            match object {
+                SelectionObject::GuardPathVertex {
+                    actor_index,
+                    vertex_index,
+                } => {
+                    if let Some(Controller::Guard(guard)) =
+                        game.actors.get_mut(actor_index).map(|a| &mut a.controller)
+                    {
+                        if guard.points.verts.len() > 3 {
+                            guard.remove_waypoint(vertex_index);
+                        }
+                    }
+                }
+                SelectionObject::PolygonVertex {
+                    polygon_type,
+                    index,
+                } => {
+                    let polygon = polygon_type.find(&mut game.game_map);
+                    if polygon.verts.len() > 3 {
+                        polygon.verts.remove(index);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Remove objects from the back, so the indices of the others do not change
+        let mut objects: Vec<SelectionObject> = selection
+            .into_iter()
+            .filter(|object| !object.is_vertex())
+            .collect();
+        objects.sort_by_key(|object| match *object {
+            SelectionObject::Actor { index } => Reverse(index),
+            SelectionObject::Polygon { polygon_type } => Reverse(polygon_type.index()),
+            _ => Reverse(0),
+        });
+        let game_map = &mut game.game_map;
+        for object in objects {
+            match object {
+                SelectionObject::Actor { index } if !game.actors[index].is_player() => {
+                    game.actors.remove(index);
+                }
+                SelectionObject::Polygon { polygon_type } => match polygon_type {
+                    PolygonType::Obstacle { index } => {
+                        game_map.obstacles.remove(index);
+                    }
+                    PolygonType::LightZone { index } => {
+                        game_map.light_zones.remove(index);
+                    }
+                    // The level needs at least one end area
+                    PolygonType::EndArea { index } if game_map.end_areas.len() > 1 => {
+                        game_map.end_areas.remove(index);
+                    }
+                    PolygonType::EndArea { .. } => {}
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+fn point_mut(game: &mut Game, point: PointRef) -> Option<&mut Point2<f32>> {
+    match point {
+        PointRef::Actor(index) => game.actors.get_mut(index).map(|actor| &mut actor.pos),
+        PointRef::GuardPath(actor_index, index) => match game.actors.get_mut(actor_index) {
+            Some(Actor {
+                controller: Controller::Guard(guard),
+                ..
+            }) => guard.points.verts.get_mut(index),
+            _ => None,
+        },
+        PointRef::Polygon(polygon_type, index) => {
+            polygon_type.find(&mut game.game_map).verts.get_mut(index)
+        }
+    }
+}
+
+fn point_ref(game: &Game, point: PointRef) -> Option<Point2<f32>> {
+    match point {
+        PointRef::Actor(index) => game.actors.get(index).map(|actor| actor.pos),
+        PointRef::GuardPath(actor_index, index) => match game.actors.get(actor_index) {
+            Some(Actor {
+                controller: Controller::Guard(guard),
+                ..
+            }) => guard.points.verts.get(index).copied(),
+            _ => None,
+        },
+        PointRef::Polygon(polygon_type, index) => {
+            polygon_type.get(&game.game_map).verts.get(index).copied()
+        }
+    }
+}
+
+/// The center of the bounding box of the points
+fn center(points: &[Point2<f32>]) -> Option<Point2<f32>> {
+    let first = points.first()?;
+    let (mut min, mut max) = (*first, *first);
+    for point in points {
+        min = min.inf(point);
+        max = max.sup(point);
+    }
+    Some(nalgebra::center(&min, &max))
+}
+
+#[cfg(test)]
+mod editor_tests {
+    use super::{PolygonType, SelectionHandler, SelectionObject, Transform};
+    use crate::game::{polygon::Polygon, Game};
+    use ggez::event::MouseButton;
+    use nalgebra::Point2;
+
+    fn square(x: f32, y: f32) -> Polygon {
+        Polygon::new(vec![
+            Point2::new(x, y),
+            Point2::new(x + 50.0, y),
+            Point2::new(x + 50.0, y + 50.0),
+            Point2::new(x, y + 50.0),
+        ])
+    }
+
+    fn box_select(
+        handler: &mut SelectionHandler,
+        game: &mut Game,
+        from: Point2<f32>,
+        to: Point2<f32>,
+    ) {
+        handler.handle_mouse_down(game, MouseButton::Left, from, false);
+        handler.handle_mouse_motion(game, to);
+        handler.handle_mouse_up(game, MouseButton::Left);
+    }
+
+    #[test]
+    fn transforms_around_pivot() {
+        let pivot = Point2::new(100.0, 100.0);
+        let point = Point2::new(150.0, 100.0);
+        let rotated = Transform::RotateRight.apply(point, pivot, true);
+        assert!((rotated - Point2::new(100.0, 150.0)).norm() < 1e-4);
+        assert_eq!(
+            Transform::ScaleUp.apply(point, pivot, true),
+            Point2::new(200.0, 100.0)
+        );
+        assert_eq!(
+            Transform::MirrorHorizontal.apply(point, pivot, false),
+            Point2::new(50.0, 100.0)
+        );
+    }
+
+    #[test]
+    fn box_selects_objects_inside() {
+        let mut game = Game::new();
+        game.game_map.obstacles.push(square(400.0, 400.0));
+        game.game_map.obstacles.push(square(600.0, 400.0));
+        let mut handler = SelectionHandler::new();
+
+        box_select(
+            &mut handler,
+            &mut game,
+            Point2::new(390.0, 390.0),
+            Point2::new(500.0, 500.0),
+        );
+        let first = SelectionObject::Polygon {
+            polygon_type: PolygonType::Obstacle { index: 0 },
+        };
+        assert_eq!(handler.selection, vec![first]);
+
+        // Vertices of selected shapes can be picked out with another box
+        box_select(
+            &mut handler,
+            &mut game,
+            Point2::new(440.0, 390.0),
+            Point2::new(460.0, 460.0),
+        );
+        assert_eq!(
+            handler.selection,
+            vec![
+                SelectionObject::PolygonVertex {
+                    polygon_type: PolygonType::Obstacle { index: 0 },
+                    index: 1,
+                },
+                SelectionObject::PolygonVertex {
+                    polygon_type: PolygonType::Obstacle { index: 0 },
+                    index: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn deletes_selected_group() {
+        let mut game = Game::new();
+        for i in 0..3 {
+            game.game_map
+                .obstacles
+                .push(square(100.0 * i as f32, 400.0));
+        }
+        let mut handler = SelectionHandler::new();
+        handler.selection = vec![
+            SelectionObject::Polygon {
+                polygon_type: PolygonType::Obstacle { index: 0 },
+            },
+            SelectionObject::Polygon {
+                polygon_type: PolygonType::Obstacle { index: 2 },
+            },
+            // The player and the last end area are kept
+            SelectionObject::Actor { index: 0 },
+            SelectionObject::Polygon {
+                polygon_type: PolygonType::EndArea { index: 0 },
+            },
+        ];
+
+        handler.delete_selection(&mut game);
+        assert_eq!(game.game_map.obstacles.len(), 1);
+        assert_eq!(
+            game.game_map.obstacles[0].verts[0],
+            Point2::new(100.0, 400.0)
+        );
+        assert_eq!(game.actors.len(), 1);
+        assert_eq!(game.game_map.end_areas.len(), 1);
+        assert!(handler.selection.is_empty());
+    }
+
+    #[test]
+    fn setting_position_moves_whole_shape() {
+        let mut game = Game::new();
+        game.game_map.obstacles.push(square(0.0, 0.0));
+        let polygon_type = PolygonType::Obstacle {
+            index: game.game_map.obstacles.len() - 1,
+        };
+        let mut handler = SelectionHandler::new();
+        handler.selection = vec![SelectionObject::Polygon { polygon_type }];
+
+        assert_eq!(handler.position(&game), Some(Point2::new(25.0, 25.0)));
+        handler.set_position(&mut game, Point2::new(125.0, 50.0));
+        let polygon = game.game_map.obstacles.last().unwrap();
+        assert_eq!(polygon.verts[0], Point2::new(100.0, 25.0));
+        assert_eq!(polygon.verts[2], Point2::new(150.0, 75.0));
     }
 }
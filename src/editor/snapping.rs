@@ -0,0 +1,173 @@
+use super::PolygonType;
+use crate::game::{controller::Controller, polygon::Polygon, Game};
+use nalgebra::{distance, Point2};
+
+/// How close the mouse has to be to a vertex, edge or axis to snap to it
+const SNAP_DISTANCE: f32 = 10.0;
+
+/// What a point was snapped to, drawn as a guide while the point is dragged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapGuide {
+    Vertex(Point2<f32>),
+    Edge(Point2<f32>, Point2<f32>),
+    /// A horizontal or vertical line from the previous vertex
+    Axis(Point2<f32>, Point2<f32>),
+}
+
+/// The object a dragged point belongs to, which it is not snapped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapSource {
+    Polygon(PolygonType),
+    GuardPath(usize),
+    /// A point that is not part of the level yet
+    New,
+}
+
+/// A point moved onto nearby geometry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snap {
+    pub point: Point2<f32>,
+    pub guide: SnapGuide,
+}
+
+fn polygons(game: &Game) -> impl Iterator<Item = (PolygonType, &Polygon)> {
+    let game_map = &game.game_map;
+    let obstacles = game_map
+        .obstacles
+        .iter()
+        .enumerate()
+        .map(|(index, polygon)| (PolygonType::Obstacle { index }, polygon));
+    let end_areas = game_map
+        .end_areas
+        .iter()
+        .enumerate()
+        .map(|(index, polygon)| (PolygonType::EndArea { index }, polygon));
+    let light_zones = game_map
+        .light_zones
+        .iter()
+        .enumerate()
+        .map(|(index, zone)| (PolygonType::LightZone { index }, &zone.polygon));
+    obstacles.chain(end_areas).chain(light_zones)
+}
+
+fn waypoints(game: &Game) -> impl Iterator<Item = (usize, &Point2<f32>)> {
+    game.actors
+        .iter()
+        .enumerate()
+        .filter_map(|(index, actor)| match &actor.controller {
+            Controller::Guard(guard) => Some((index, &guard.points.verts)),
+            _ => None,
+        })
+        .flat_map(|(index, verts)| verts.iter().map(move |vert| (index, vert)))
+}
+
+/// The point on the segment from `a` to `b` closest to `point`
+fn closest_on_segment(a: Point2<f32>, b: Point2<f32>, point: Point2<f32>) -> Point2<f32> {
+    let edge = b - a;
+    let length_squared = edge.norm_squared();
+    if length_squared == 0.0 {
+        return a;
+    }
+    let t = ((point - a).dot(&edge) / length_squared).clamp(0.0, 1.0);
+    a + edge * t
+}
+
+/// Snap a dragged point to the nearest vertex or guard waypoint, or else to the nearest edge,
+/// or else in line with the previous vertex, ignoring the object the point belongs to
+pub fn snap_point(
+    game: &Game,
+    pos: Point2<f32>,
+    source: SnapSource,
+    previous: Option<Point2<f32>>,
+) -> Option<Snap> {
+    let other_polygons = || polygons(game).filter(|(p, _)| source != SnapSource::Polygon(*p));
+    let nearest = |snaps: &mut dyn Iterator<Item = Snap>| {
+        snaps
+            .map(|snap| (distance(&snap.point, &pos), snap))
+            .filter(|(d, _)| *d < SNAP_DISTANCE)
+            .min_by(|(d1, _), (d2, _)| d1.total_cmp(d2))
+            .map(|(_, snap)| snap)
+    };
+
+    let polygon_verts = other_polygons().flat_map(|(_, polygon)| polygon.verts.iter());
+    let guard_verts = waypoints(game)
+        .filter(|(index, _)| source != SnapSource::GuardPath(*index))
+        .map(|(_, vert)| vert);
+    let mut vertices = polygon_verts.chain(guard_verts).map(|&vert| Snap {
+        point: vert,
+        guide: SnapGuide::Vertex(vert),
+    });
+    if let Some(snap) = nearest(&mut vertices) {
+        return Some(snap);
+    }
+
+    let mut edges = other_polygons()
+        .flat_map(|(_, polygon)| polygon.edges())
+        .map(|(a, b)| Snap {
+            point: closest_on_segment(a, b, pos),
+            guide: SnapGuide::Edge(a, b),
+        });
+    if let Some(snap) = nearest(&mut edges) {
+        return Some(snap);
+    }
+
+    let previous = previous?;
+    let mut axes = [
+        Point2::new(previous.x, pos.y),
+        Point2::new(pos.x, previous.y),
+    ]
+    .into_iter()
+    .map(|point| Snap {
+        point,
+        guide: SnapGuide::Axis(previous, point),
+    });
+    nearest(&mut axes)
+}
+
+#[cfg(test)]
+mod snapping_tests {
+    use super::{snap_point, SnapGuide, SnapSource};
+    use crate::{
+        editor::PolygonType,
+        game::{polygon::Polygon, Game},
+    };
+    use nalgebra::Point2;
+
+    fn game_with_square() -> Game {
+        let mut game = Game::new();
+        game.game_map.obstacles.push(Polygon::new(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(100.0, 0.0),
+            Point2::new(100.0, 100.0),
+            Point2::new(0.0, 100.0),
+        ]));
+        game
+    }
+
+    #[test]
+    fn vertices_are_preferred_over_edges() {
+        let game = game_with_square();
+        let snap = snap_point(&game, Point2::new(96.0, 5.0), SnapSource::New, None).unwrap();
+        assert_eq!(snap.point, Point2::new(100.0, 0.0));
+
+        let snap = snap_point(&game, Point2::new(50.0, 104.0), SnapSource::New, None).unwrap();
+        assert_eq!(snap.point, Point2::new(50.0, 100.0));
+        assert!(matches!(snap.guide, SnapGuide::Edge(..)));
+
+        assert_eq!(
+            snap_point(&game, Point2::new(50.0, 50.0), SnapSource::New, None),
+            None
+        );
+    }
+
+    #[test]
+    fn dragged_polygon_is_ignored() {
+        let game = game_with_square();
+        let source = SnapSource::Polygon(PolygonType::Obstacle { index: 0 });
+        let previous = Some(Point2::new(0.0, 100.0));
+        let snap = snap_point(&game, Point2::new(3.0, 40.0), source, previous).unwrap();
+        // Only the axis through the previous vertex is left to snap to
+        assert_eq!(snap.point, Point2::new(0.0, 40.0));
+        assert!(matches!(snap.guide, SnapGuide::Axis(..)));
+    }
+}
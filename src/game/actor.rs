@@ -1,10 +1,21 @@
 use super::fov::{ConeFieldOfView, NoFieldOfView};
-use nalgebra::{Point2, Unit, Vector2};
+use nalgebra::{Point2, Rotation2, Unit, Vector2};
 use {
-    crate::game::{controller::Controller, fov::FieldOfView, game_map::GameMap},
+    crate::{
+        controls::Controls,
+        game::{
+            controller::Controller,
+            fov::FieldOfView,
+            game_map::GameMap,
+            level_info::{GuardSettings, PatrolMode},
+        },
+    },
     ggez::Context,
 };
 
+/// Actors slower than this are standing still, any slower movement is only rounding errors
+const STILL_SPEED: f32 = 1e-3;
+
 pub struct Actor {
     pub pos: Point2<f32>,
     pub radius: f32,
@@ -12,7 +23,18 @@ pub struct Actor {
     pub fov: Box<dyn FieldOfView>,
     pub controller: Controller,
     pub move_speed: f32,
+    /// How fast the actor turns, in degrees per second
+    pub turn_speed: f32,
     pub discovered_player: f32,
+    pub velocity: Vector2<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Movement {
+    Still,
+    Creeping,
+    Moving,
+    Sprinting,
 }
 
 impl Actor {
@@ -29,7 +51,9 @@ impl Actor {
             fov,
             controller,
             move_speed,
+            turn_speed: 720.0,
             discovered_player: 0.0,
+            velocity: Vector2::new(0.0, 0.0),
         }
     }
 
@@ -43,24 +67,125 @@ impl Actor {
     }
 
     pub fn new_guard(x: f32, y: f32, patrol_points: Vec<Point2<f32>>) -> Self {
-        Actor::new(
+        let settings = GuardSettings::default();
+        let mut guard = Actor::new(
             Point2::new(x, y),
-            Box::new(ConeFieldOfView::new(90.0, 300.0)),
+            Box::new(ConeFieldOfView::new(
+                settings.view_angle,
+                settings.view_distance,
+            )),
             Controller::new_guard(patrol_points, 0),
-            1.3,
-        )
+            settings.move_speed,
+        );
+        guard.turn_speed = settings.turn_speed;
+        guard
+    }
+
+    pub fn guard_settings(&self) -> GuardSettings {
+        let (stop_to_turn, patrol_mode, waypoints) = match &self.controller {
+            Controller::Guard(guard) => (guard.stop_to_turn, guard.mode, guard.waypoints.clone()),
+            Controller::Player(_) => (false, PatrolMode::default(), Vec::new()),
+        };
+        GuardSettings {
+            move_speed: self.move_speed,
+            view_angle: self.fov.view_angle(),
+            view_distance: self.fov.view_distance(),
+            facing: self.direction.y.atan2(self.direction.x).to_degrees(),
+            turn_speed: self.turn_speed,
+            stop_to_turn,
+            patrol_mode,
+            waypoints,
+        }
+    }
+
+    pub fn apply_guard_settings(&mut self, settings: &GuardSettings) {
+        self.move_speed = settings.move_speed;
+        self.fov = Box::new(ConeFieldOfView::new(
+            settings.view_angle,
+            settings.view_distance,
+        ));
+        self.turn_speed = settings.turn_speed;
+        let facing = settings.facing.to_radians();
+        self.direction = Unit::new_normalize(Vector2::new(facing.cos(), facing.sin()));
+        if let Controller::Guard(guard) = &mut self.controller {
+            guard.stop_to_turn = settings.stop_to_turn;
+            guard.mode = settings.patrol_mode;
+            guard.waypoints = settings.waypoints.clone();
+        }
+    }
+
+    /// Turn towards `target`, by at most `max_angle` radians
+    pub fn turn_towards(&mut self, target: Unit<Vector2<f32>>, max_angle: f32) {
+        let angle = self
+            .direction
+            .perp(&target)
+            .atan2(self.direction.dot(&target));
+        if angle.abs() <= max_angle {
+            self.direction = target;
+        } else {
+            let rotation = Rotation2::new(max_angle * angle.signum());
+            self.direction = Unit::new_normalize(rotation * self.direction.into_inner());
+        }
     }
 
     pub fn is_player(&self) -> bool {
         matches!(self.controller, Controller::Player(_))
     }
 
-    pub fn next_movement(&mut self, ctx: &Context) -> Vector2<f32> {
+    /// Classify how the actor moved during the last tick
+    pub fn movement(&self) -> Movement {
+        let speed = self.velocity.magnitude();
+        if speed < STILL_SPEED {
+            Movement::Still
+        } else if speed < 0.5 * self.move_speed {
+            Movement::Creeping
+        } else if speed > 1.5 * self.move_speed {
+            Movement::Sprinting
+        } else {
+            Movement::Moving
+        }
+    }
+
+    pub fn next_movement(&mut self, ctx: &Context, controls: &Controls) -> Vector2<f32> {
         self.controller
-            .next_movement(ctx, self.pos, self.move_speed)
+            .next_movement(ctx, self.pos, self.move_speed, controls)
     }
 
     pub fn update_fov(&mut self, game_map: &GameMap) {
         self.fov.recalculate(self.pos, self.direction, game_map)
     }
 }
+
+#[cfg(test)]
+mod actor_tests {
+    use super::{Actor, Movement};
+    use nalgebra::{Unit, Vector2};
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn turning_is_limited_to_max_angle() {
+        let mut actor = Actor::new_player(0.0, 0.0);
+        let down = Unit::new_normalize(Vector2::new(0.0, 1.0));
+
+        actor.turn_towards(down, FRAC_PI_2 / 3.0);
+        let angle = actor.direction.y.atan2(actor.direction.x);
+        assert!((angle - FRAC_PI_2 / 3.0).abs() < 1e-5);
+
+        actor.turn_towards(down, FRAC_PI_2);
+        assert_eq!(actor.direction, down);
+    }
+
+    #[test]
+    fn slowest_movement_is_creeping() {
+        let mut actor = Actor::new_player(0.0, 0.0);
+        actor.velocity = Vector2::zeros();
+        assert_eq!(actor.movement(), Movement::Still);
+
+        actor.velocity = Vector2::new(0.05, 0.0);
+        assert_eq!(actor.movement(), Movement::Creeping);
+        actor.velocity = Vector2::new(0.5 * actor.move_speed - 0.01, 0.0);
+        assert_eq!(actor.movement(), Movement::Creeping);
+        actor.velocity = Vector2::new(actor.move_speed, 0.0);
+        assert_eq!(actor.movement(), Movement::Moving);
+    }
+}
@@ -1,30 +1,53 @@
 use nalgebra::{distance, Point2, Unit, Vector2};
 use {
     crate::game::{
-        actor::Actor, controller::Controller, game_map::GameMap, polygon::Polygon, Game,
+        actor::Actor,
+        controller::{Controller, TICK_TIME},
+        game_map::GameMap,
+        polygon::Polygon,
+        Game,
     },
     ggez::Context,
 };
 
 pub fn apply_physics_movement(game: &mut Game, ctx: &Context) {
     for actor in &mut game.actors {
-        let delta = actor.next_movement(ctx);
+        let mut delta = actor.next_movement(ctx, &game.controls);
 
-        let next_pos = &mut (actor.pos + delta);
+        // Actors turn towards where they are going, or guards waiting at a waypoint towards
+        // where they look, no faster than their turn speed
+        let max_angle = actor.turn_speed.to_radians() * TICK_TIME;
         if delta.magnitude() > 0.0 {
-            actor.direction = Unit::new_normalize(delta);
+            let target = Unit::new_normalize(delta);
+            actor.turn_towards(target, max_angle);
+            if stops_to_turn(actor) && actor.direction != target {
+                delta = Vector2::zeros();
+            }
+        } else if let Controller::Guard(guard) = &actor.controller {
+            if let Some(direction) = guard.look_direction() {
+                actor.turn_towards(direction, max_angle);
+            }
         }
 
+        let next_pos = &mut (actor.pos + delta);
+
         handle_obstacle_collisions(&game.game_map, actor, next_pos);
 
         if let Controller::Player(_) = actor.controller {
-            game.player_won = did_player_win(&game.game_map, actor, *next_pos);
+            game.player_won =
+                game.objectives.exit_active() && did_player_win(&game.game_map, actor, *next_pos);
         }
 
+        actor.velocity = *next_pos - actor.pos;
         actor.pos = *next_pos;
     }
 }
 
+/// Whether the actor is a guard that only walks on once it faces where it is going
+fn stops_to_turn(actor: &Actor) -> bool {
+    matches!(&actor.controller, Controller::Guard(guard) if guard.stop_to_turn)
+}
+
 fn handle_obstacle_collisions(game_map: &GameMap, actor: &mut Actor, next_pos: &mut Point2<f32>) {
     // TODO: Find out if there is a better way than... whatever this is.
     let mut iterations = 0;
@@ -61,7 +84,7 @@ fn move_out_of_obstacle(obstacle: &Polygon, player: &Actor, next_pos: &mut Point
 
 fn get_closest_point_on_polygon(polygon: &Polygon, point: Point2<f32>) -> Option<Point2<f32>> {
     let mut max_closest = None;
-    let mut max_closest_dist = std::f32::MAX;
+    let mut max_closest_dist = f32::MAX;
     for (start, end) in polygon.edges() {
         let closest = &get_closest_point(start, end, point);
         let dist = distance(closest, &point);
@@ -79,20 +102,20 @@ fn get_closest_point(a: Point2<f32>, b: Point2<f32>, p: Point2<f32>) -> Point2<f
 
     let ap_ab = ap.x * ab.x + ap.y * ab.y;
     let ab2 = ab.x * ab.x + ab.y * ab.y;
-    let t = (ap_ab / ab2).max(0.0).min(1.0);
+    let t = (ap_ab / ab2).clamp(0.0, 1.0);
 
     a + ab * t
 }
 
 fn did_player_win(game_map: &GameMap, player: &Actor, next_pos: Point2<f32>) -> bool {
-    let end_area = &game_map.end_area;
-
-    for (a, b) in end_area.edges() {
-        let closest_point = get_closest_point(a, b, next_pos);
-
-        let dist = distance(&closest_point, &next_pos);
-        if dist < player.radius {
-            return true;
+    for end_area in &game_map.end_areas {
+        for (a, b) in end_area.edges() {
+            let closest_point = get_closest_point(a, b, next_pos);
+
+            let dist = distance(&closest_point, &next_pos);
+            if dist < player.radius {
+                return true;
+            }
         }
     }
 
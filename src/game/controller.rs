@@ -1,7 +1,14 @@
-use ggez::{input::keyboard::KeyCode, Context};
+use ggez::Context;
 use nalgebra::{distance, Point2, Unit, Vector2};
 
-use super::polygon::Polygon;
+use super::{
+    level_info::{PatrolMode, WaypointSettings},
+    polygon::Polygon,
+};
+use crate::controls::{Action, Controls};
+
+/// How long one tick of the game takes, in seconds
+pub const TICK_TIME: f32 = 1.0 / 60.0;
 
 pub enum Controller {
     Player(PlayerController),
@@ -16,7 +23,14 @@ impl Controller {
     pub fn new_guard(points: Vec<Point2<f32>>, i: usize) -> Self {
         Controller::Guard(GuardController {
             points: Polygon::new(points),
+            mode: PatrolMode::default(),
+            waypoints: Vec::new(),
+            stop_to_turn: false,
             i,
+            backwards: false,
+            finished: false,
+            wait_left: 0.0,
+            random_state: 0x9E37_79B9,
         })
     }
 
@@ -25,10 +39,11 @@ impl Controller {
         ctx: &Context,
         pos: Point2<f32>,
         move_speed: f32,
+        controls: &Controls,
     ) -> Vector2<f32> {
         match self {
-            Controller::Player(player) => player.next_movement(ctx, move_speed),
-            Controller::Guard(guard) => guard.next_movement(ctx, pos, move_speed),
+            Controller::Player(player) => player.next_movement(ctx, move_speed, controls),
+            Controller::Guard(guard) => guard.next_movement(pos, move_speed),
         }
     }
 }
@@ -36,44 +51,169 @@ impl Controller {
 pub struct PlayerController {}
 
 impl PlayerController {
-    fn next_movement(&mut self, ctx: &Context, move_speed: f32) -> Vector2<f32> {
+    fn next_movement(
+        &mut self,
+        ctx: &Context,
+        move_speed: f32,
+        controls: &Controls,
+    ) -> Vector2<f32> {
         let mut dx = 0.0;
         let mut dy = 0.0;
-        if ctx.keyboard.is_key_pressed(KeyCode::W) {
+        if controls.is_pressed(ctx, Action::MoveUp) {
             dy -= 1.0;
         }
-        if ctx.keyboard.is_key_pressed(KeyCode::S) {
+        if controls.is_pressed(ctx, Action::MoveDown) {
             dy += 1.0;
         }
-        if ctx.keyboard.is_key_pressed(KeyCode::A) {
+        if controls.is_pressed(ctx, Action::MoveLeft) {
             dx -= 1.0;
         }
-        if ctx.keyboard.is_key_pressed(KeyCode::D) {
+        if controls.is_pressed(ctx, Action::MoveRight) {
             dx += 1.0;
         }
         let direction = Vector2::new(dx, dy);
+
+        // Digital input moves at full speed, while the stick allows creeping slowly
+        let direction = if direction.x == 0.0 && direction.y == 0.0 {
+            controls.analog_movement(ctx)
+        } else {
+            direction.normalize()
+        };
         if direction.x == 0.0 && direction.y == 0.0 {
             return direction;
         }
-        let normalized_direction = direction.normalize();
-        let move_speed = if ctx.keyboard.is_key_pressed(KeyCode::LShift) {
+
+        let move_speed = if controls.is_pressed(ctx, Action::Sprint) {
             2.0 * move_speed
         } else {
             move_speed
         };
-        normalized_direction * move_speed
+        direction * move_speed
     }
 }
 
 pub struct GuardController {
     pub points: Polygon,
+    pub mode: PatrolMode,
+    /// What to do at each waypoint, waypoints without an entry are passed without stopping
+    pub waypoints: Vec<WaypointSettings>,
+    /// Whether the guard only walks on once it faces where it is going
+    pub stop_to_turn: bool,
+    /// The waypoint the guard is walking to or waiting at
     i: usize,
+    /// Whether a ping-pong patrol is walking the path backwards
+    backwards: bool,
+    /// Whether a one-way patrol reached the end
+    finished: bool,
+    /// Seconds left to wait at the current waypoint
+    wait_left: f32,
+    /// State of the generator picking random waypoints, so patrols are the same every time
+    random_state: u32,
 }
 
 impl GuardController {
-    fn next_movement(&mut self, _ctx: &Context, pos: Point2<f32>, move_speed: f32) -> Vector2<f32> {
+    fn waypoint(&self, index: usize) -> Option<&WaypointSettings> {
+        self.waypoints.get(index)
+    }
+
+    /// Add a waypoint before `index`, keeping the settings of the others
+    pub fn insert_waypoint(&mut self, index: usize, point: Point2<f32>) {
+        self.points.verts.insert(index, point);
+        if index < self.waypoints.len() {
+            self.waypoints.insert(index, WaypointSettings::default());
+        }
+    }
+
+    /// Remove a waypoint and its settings
+    pub fn remove_waypoint(&mut self, index: usize) {
+        self.points.verts.remove(index);
+        if index < self.waypoints.len() {
+            self.waypoints.remove(index);
+        }
+    }
+
+    /// The direction to look in while waiting at a waypoint
+    pub fn look_direction(&self) -> Option<Unit<Vector2<f32>>> {
+        if self.wait_left <= 0.0 {
+            return None;
+        }
+        let waypoint = self.waypoint(self.i)?;
+        let count = waypoint.looks.len();
+        if count == 0 {
+            return None;
+        }
+        let waited = waypoint.wait - self.wait_left;
+        let look = ((waited * count as f32 / waypoint.wait) as usize).min(count - 1);
+        let angle = waypoint.looks[look].to_radians();
+        Some(Unit::new_normalize(Vector2::new(angle.cos(), angle.sin())))
+    }
+
+    fn next_random(&mut self) -> u32 {
+        // Xorshift
+        let mut x = self.random_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.random_state = x;
+        x
+    }
+
+    /// Pick the next waypoint to walk to
+    fn advance(&mut self) {
+        let count = self.points.verts.len();
+        if count < 2 {
+            return;
+        }
+
+        self.i = match self.mode {
+            PatrolMode::Loop => (self.i + 1) % count,
+            PatrolMode::PingPong => {
+                if self.i == count - 1 {
+                    self.backwards = true;
+                } else if self.i == 0 {
+                    self.backwards = false;
+                }
+                if self.backwards {
+                    self.i - 1
+                } else {
+                    self.i + 1
+                }
+            }
+            PatrolMode::Random => {
+                // Any waypoint except the current one
+                let offset = 1 + self.next_random() as usize % (count - 1);
+                (self.i + offset) % count
+            }
+            PatrolMode::OneWay if self.i == count - 1 => {
+                self.finished = true;
+                self.i
+            }
+            PatrolMode::OneWay => self.i + 1,
+        };
+    }
+
+    fn next_movement(&mut self, pos: Point2<f32>, move_speed: f32) -> Vector2<f32> {
+        if self.wait_left > 0.0 {
+            self.wait_left -= TICK_TIME;
+            if self.wait_left <= 0.0 {
+                self.advance();
+            }
+            return Vector2::zeros();
+        }
+        if self.finished {
+            return Vector2::zeros();
+        }
+
         if distance(&pos, &self.points.verts[self.i]) <= 5.0 {
-            self.i = (self.i + 1) % self.points.verts.len();
+            let wait = self.waypoint(self.i).map_or(0.0, |waypoint| waypoint.wait);
+            if wait > 0.0 {
+                self.wait_left = wait;
+                return Vector2::zeros();
+            }
+            self.advance();
+        }
+        if self.finished {
+            return Vector2::zeros();
         }
         if distance(&pos, &self.points.verts[self.i]) <= 2.0 {
             self.points.verts[self.i] - pos
@@ -83,3 +223,73 @@ impl GuardController {
         }
     }
 }
+
+#[cfg(test)]
+mod controller_tests {
+    use super::{Controller, GuardController, TICK_TIME};
+    use crate::game::level_info::{PatrolMode, WaypointSettings};
+    use nalgebra::{Point2, Vector2};
+
+    fn guard(mode: PatrolMode) -> GuardController {
+        let points = (0..3).map(|i| Point2::new(i as f32 * 100.0, 0.0)).collect();
+        match Controller::new_guard(points, 0) {
+            Controller::Guard(mut guard) => {
+                guard.mode = mode;
+                guard
+            }
+            Controller::Player(_) => unreachable!(),
+        }
+    }
+
+    /// The waypoints visited after the first one
+    fn visits(guard: &mut GuardController, count: usize) -> Vec<usize> {
+        (0..count)
+            .map(|_| {
+                guard.advance();
+                guard.i
+            })
+            .collect()
+    }
+
+    #[test]
+    fn patrol_modes_pick_next_waypoint() {
+        assert_eq!(visits(&mut guard(PatrolMode::Loop), 4), vec![1, 2, 0, 1]);
+        assert_eq!(
+            visits(&mut guard(PatrolMode::PingPong), 5),
+            vec![1, 2, 1, 0, 1]
+        );
+
+        let mut one_way = guard(PatrolMode::OneWay);
+        assert_eq!(visits(&mut one_way, 3), vec![1, 2, 2]);
+        assert!(one_way.finished);
+
+        let mut random = guard(PatrolMode::Random);
+        let mut last = random.i;
+        for next in visits(&mut random, 20) {
+            assert_ne!(next, last);
+            last = next;
+        }
+    }
+
+    #[test]
+    fn waits_and_looks_at_waypoint() {
+        let mut guard = guard(PatrolMode::Loop);
+        guard.waypoints = vec![WaypointSettings {
+            wait: 10.0 * TICK_TIME,
+            looks: vec![90.0, 180.0],
+        }];
+        let pos = Point2::new(0.0, 0.0);
+
+        // Arriving at the waypoint starts the wait, looking down and then left
+        assert_eq!(guard.next_movement(pos, 1.0), Vector2::zeros());
+        let mut looks = Vec::new();
+        while let Some(direction) = guard.look_direction() {
+            looks.push(direction.y > 0.99);
+            assert_eq!(guard.next_movement(pos, 1.0), Vector2::zeros());
+        }
+        assert!((10..=11).contains(&looks.len()));
+        assert!(looks[..5].iter().all(|&down| down));
+        assert!(looks[6..].iter().all(|&down| !down));
+        assert_eq!(guard.i, 1);
+    }
+}
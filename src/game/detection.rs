@@ -0,0 +1,96 @@
+use crate::game::actor::Movement;
+use serde::{Deserialize, Serialize};
+
+/// Per-level tuning of how quickly guards discover the player.
+///
+/// While a guard can see the player, its detection meter grows by
+/// `base_rate * light factor * distance factor * movement factor` every tick,
+/// and the player is discovered when the meter reaches 1.0.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DetectionSettings {
+    /// Detection gained per tick in full light, at point blank range, while moving
+    pub base_rate: f32,
+    /// Detection lost per tick while the player is out of sight
+    pub decay_rate: f32,
+    /// Light level of the parts of the map not covered by a light zone
+    pub ambient_light: f32,
+    /// Lower bound of the light factor, so darkness alone never makes the player invisible
+    pub min_light_factor: f32,
+    /// How much detection slows down at the edge of the guard's view distance
+    pub distance_falloff: f32,
+    pub still_factor: f32,
+    pub creeping_factor: f32,
+    pub moving_factor: f32,
+    pub sprinting_factor: f32,
+}
+
+impl Default for DetectionSettings {
+    fn default() -> Self {
+        DetectionSettings {
+            base_rate: 0.015, // approx 60 ticks = 1 second to discover
+            decay_rate: 0.015,
+            ambient_light: 1.0,
+            min_light_factor: 0.1,
+            distance_falloff: 0.5,
+            still_factor: 0.5,
+            creeping_factor: 0.7,
+            moving_factor: 1.0,
+            sprinting_factor: 1.5,
+        }
+    }
+}
+
+impl DetectionSettings {
+    /// Get the detection gained in one tick, where `distance_ratio` is the distance
+    /// to the guard relative to its view distance
+    pub fn detection_rate(&self, light_level: f32, distance_ratio: f32, movement: Movement) -> f32 {
+        let light_factor = light_level.clamp(self.min_light_factor, 1.0);
+        let distance_factor = 1.0 - self.distance_falloff * distance_ratio.clamp(0.0, 1.0);
+        let movement_factor = match movement {
+            Movement::Still => self.still_factor,
+            Movement::Creeping => self.creeping_factor,
+            Movement::Moving => self.moving_factor,
+            Movement::Sprinting => self.sprinting_factor,
+        };
+
+        self.base_rate * light_factor * distance_factor * movement_factor
+    }
+}
+
+#[cfg(test)]
+mod detection_tests {
+    use super::{DetectionSettings, Movement};
+
+    #[test]
+    fn full_light_point_blank() {
+        let settings = DetectionSettings::default();
+        let rate = settings.detection_rate(1.0, 0.0, Movement::Moving);
+        assert_eq!(rate, settings.base_rate);
+    }
+
+    #[test]
+    fn darkness_is_clamped() {
+        let settings = DetectionSettings::default();
+        let dark = settings.detection_rate(0.0, 0.0, Movement::Moving);
+        assert_eq!(dark, settings.base_rate * settings.min_light_factor);
+    }
+
+    #[test]
+    fn sprinting_far_away() {
+        let settings = DetectionSettings::default();
+        let near = settings.detection_rate(1.0, 0.0, Movement::Sprinting);
+        let far = settings.detection_rate(1.0, 1.0, Movement::Sprinting);
+        let walking = settings.detection_rate(1.0, 1.0, Movement::Moving);
+        assert!(far < near);
+        assert!(walking < far);
+    }
+
+    #[test]
+    fn creeping_is_less_visible() {
+        let settings = DetectionSettings::default();
+        let creeping = settings.detection_rate(1.0, 0.5, Movement::Creeping);
+        let moving = settings.detection_rate(1.0, 0.5, Movement::Moving);
+        assert!(creeping < moving);
+    }
+}
@@ -17,6 +17,9 @@ pub trait FieldOfView {
         game_map: &GameMap,
     );
     fn is_inside_fov(&self, game_map: &GameMap, point: Point2<f32>) -> bool;
+    fn view_distance(&self) -> f32;
+    /// The full width of the view cone in degrees
+    fn view_angle(&self) -> f32;
 }
 
 pub struct ConeFieldOfView {
@@ -147,6 +150,14 @@ impl FieldOfView for ConeFieldOfView {
 
         hit.is_none()
     }
+
+    fn view_distance(&self) -> f32 {
+        self.view_distance
+    }
+
+    fn view_angle(&self) -> f32 {
+        self.view_angle.to_degrees()
+    }
 }
 
 fn signed_angle(v1: Vector2<f32>, v2: Vector2<f32>) -> f32 {
@@ -174,4 +185,12 @@ impl FieldOfView for NoFieldOfView {
     fn is_inside_fov(&self, _game_map: &GameMap, _point: Point2<f32>) -> bool {
         false
     }
+
+    fn view_distance(&self) -> f32 {
+        0.0
+    }
+
+    fn view_angle(&self) -> f32 {
+        0.0
+    }
 }
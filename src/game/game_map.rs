@@ -1,15 +1,51 @@
 use crate::game::polygon::Polygon;
+use nalgebra::Point2;
 
 pub struct GameMap {
     pub obstacles: Vec<Polygon>,
-    pub end_area: Polygon,
+    pub end_areas: Vec<Polygon>,
+    pub light_zones: Vec<LightZone>,
 }
 
 impl GameMap {
-    pub fn new(obstacles: Vec<Polygon>, end_area: Polygon) -> Self {
+    pub fn new(
+        obstacles: Vec<Polygon>,
+        end_areas: Vec<Polygon>,
+        light_zones: Vec<LightZone>,
+    ) -> Self {
         GameMap {
             obstacles,
-            end_area,
+            end_areas,
+            light_zones,
+        }
+    }
+
+    /// Get the light level at a point, which is the brightest light zone covering it,
+    /// or the ambient light level if there are none
+    pub fn light_level_at(&self, point: Point2<f32>, ambient_light: f32) -> f32 {
+        self.light_zones
+            .iter()
+            .filter(|zone| zone.polygon.encloses(point))
+            .map(|zone| zone.light_level)
+            .reduce(f32::max)
+            .unwrap_or(ambient_light)
+    }
+}
+
+/// Light level of new dark zones
+pub const DEFAULT_LIGHT_LEVEL: f32 = 0.2;
+
+/// A region of the map with its own light level, from 0.0 (pitch black) to 1.0 (fully lit)
+pub struct LightZone {
+    pub polygon: Polygon,
+    pub light_level: f32,
+}
+
+impl LightZone {
+    pub fn new(polygon: Polygon, light_level: f32) -> Self {
+        LightZone {
+            polygon,
+            light_level,
         }
     }
 }
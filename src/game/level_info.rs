@@ -1,11 +1,187 @@
-use serde::{Deserialize, Serialize};
+use crate::game::{detection::DetectionSettings, objective::ObjectiveData};
+use serde::{Deserialize, Deserializer, Serialize};
 
 type Point = (f32, f32);
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct LevelInfo {
     pub player_data: Point,
     pub guard_data: Vec<(Point, Vec<Point>)>,
     pub obstacle_data: Vec<Vec<Point>>,
     pub end_area_data: Vec<Point>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_end_area_data: Vec<Vec<Point>>,
+    #[serde(default)]
+    pub light_zone_data: Vec<(f32, Vec<Point>)>,
+    #[serde(default)]
+    pub detection: DetectionSettings,
+    #[serde(default)]
+    pub objectives: Vec<ObjectiveData>,
+    #[serde(default)]
+    pub ordered_objectives: bool,
+    /// Settings for each guard in `guard_data`, guards without an entry use the defaults
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub guard_settings: Vec<GuardSettings>,
+    #[serde(default)]
+    pub metadata: LevelMetadata,
+}
+
+/// Information shown in the level selector, which does not change how the level plays
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelMetadata {
+    /// Shown instead of the name made from the file name
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub author: String,
+    /// From 1 for the easiest levels to 5 for the hardest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<u8>,
+    /// The time in seconds a good run takes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub par_time: Option<f32>,
+    /// Short keywords like "patrols" or "items"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl LevelMetadata {
+    /// Parse comma separated tags, ignoring empty ones
+    pub fn parse_tags(text: &str) -> Vec<String> {
+        text.split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuardSettings {
+    pub move_speed: f32,
+    /// The full width of the view cone in degrees
+    pub view_angle: f32,
+    pub view_distance: f32,
+    /// The direction the guard faces at the start, in degrees clockwise from the right
+    pub facing: f32,
+    /// How fast the guard turns, in degrees per second
+    pub turn_speed: f32,
+    /// Whether the guard stops at corners of its patrol path until it faces the next waypoint
+    pub stop_to_turn: bool,
+    pub patrol_mode: PatrolMode,
+    /// What the guard does at each waypoint of its patrol path, waypoints without an entry
+    /// are passed without stopping
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub waypoints: Vec<WaypointSettings>,
+}
+
+impl Default for GuardSettings {
+    fn default() -> Self {
+        GuardSettings {
+            move_speed: 1.3,
+            view_angle: 90.0,
+            view_distance: 300.0,
+            facing: 0.0,
+            turn_speed: 180.0,
+            stop_to_turn: false,
+            patrol_mode: PatrolMode::Loop,
+            waypoints: Vec::new(),
+        }
+    }
+}
+
+/// The order in which a guard visits the waypoints of its patrol path
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatrolMode {
+    /// Go back to the first waypoint after the last one
+    #[default]
+    Loop,
+    /// Walk the path backwards after reaching either end
+    PingPong,
+    /// Go to a random other waypoint each time
+    Random,
+    /// Stop at the last waypoint
+    OneWay,
+}
+
+impl PatrolMode {
+    pub const ALL: [PatrolMode; 4] = [
+        PatrolMode::Loop,
+        PatrolMode::PingPong,
+        PatrolMode::Random,
+        PatrolMode::OneWay,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PatrolMode::Loop => "Loop",
+            PatrolMode::PingPong => "Back and forth",
+            PatrolMode::Random => "Random",
+            PatrolMode::OneWay => "One way",
+        }
+    }
+
+    /// Whether the guard walks from the last waypoint back to the first
+    pub fn is_closed(self) -> bool {
+        matches!(self, PatrolMode::Loop | PatrolMode::Random)
+    }
+}
+
+/// What a guard does when it reaches a waypoint
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaypointSettings {
+    /// How many seconds the guard waits before moving on
+    pub wait: f32,
+    /// Where the guard looks while it waits, in degrees clockwise from the right. The guard
+    /// turns to each direction in turn, spending an equal part of the wait on each.
+    #[serde(
+        alias = "look",
+        deserialize_with = "one_or_more_looks",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub looks: Vec<f32>,
+}
+
+/// Levels saved before guards could look in several directions have a single `look`
+fn one_or_more_looks<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Looks {
+        One(f32),
+        More(Vec<f32>),
+    }
+
+    Ok(match Looks::deserialize(deserializer)? {
+        Looks::One(look) => vec![look],
+        Looks::More(looks) => looks,
+    })
+}
+
+#[cfg(test)]
+mod level_info_tests {
+    use super::{LevelMetadata, WaypointSettings};
+
+    #[test]
+    fn tags_are_trimmed() {
+        let tags = LevelMetadata::parse_tags(" patrols,items , ,lights");
+        assert_eq!(tags, vec!["patrols", "items", "lights"]);
+    }
+
+    #[test]
+    fn single_look_is_read_as_list() {
+        let waypoint: WaypointSettings =
+            serde_json::from_str(r#"{"wait":2.0,"look":90.0}"#).unwrap();
+        assert_eq!(waypoint.looks, vec![90.0]);
+        let waypoint: WaypointSettings =
+            serde_json::from_str(r#"{"wait":2.0,"looks":[90.0,180.0]}"#).unwrap();
+        assert_eq!(waypoint.looks, vec![90.0, 180.0]);
+        let waypoint: WaypointSettings = serde_json::from_str(r#"{"wait":2.0}"#).unwrap();
+        assert!(waypoint.looks.is_empty());
+    }
 }
@@ -1,29 +1,51 @@
 pub mod actor;
 pub mod collision_handling;
 pub mod controller;
+pub mod detection;
 pub mod fov;
 pub mod game_map;
 pub mod level_info;
+pub mod objective;
 pub mod polygon;
 pub mod raycast;
 pub mod rendering;
+pub mod stats;
 
 use crate::{
+    controls::{Action, Controls},
     game::{controller::Controller, polygon::Polygon},
-    view::ViewEvent,
 };
 
 use self::{
-    actor::Actor, collision_handling::apply_physics_movement, game_map::GameMap,
-    level_info::LevelInfo,
+    actor::Actor,
+    collision_handling::apply_physics_movement,
+    detection::DetectionSettings,
+    game_map::{GameMap, LightZone},
+    level_info::{LevelInfo, LevelMetadata},
+    objective::{ObjectiveState, Objectives},
+    stats::RunStats,
 };
 use ggez::Context;
-use nalgebra::Point2;
+use nalgebra::{distance, Point2};
 
 pub struct Game {
     pub actors: Vec<Actor>,
     pub game_map: GameMap,
     pub player_won: bool,
+    pub detection: DetectionSettings,
+    pub objectives: Objectives,
+    pub stats: RunStats,
+    pub controls: Controls,
+    /// Kept so it is saved again by the editor
+    pub metadata: LevelMetadata,
+}
+
+/// How an attempt at a level ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Won,
+    Discovered,
+    ObjectiveFailed,
 }
 
 impl Game {
@@ -37,8 +59,13 @@ impl Game {
 
         Game {
             actors: vec![Actor::new_player(50.0, 50.0)],
-            game_map: GameMap::new(vec![], end_area),
+            game_map: GameMap::new(vec![], vec![end_area], vec![]),
             player_won: false,
+            detection: DetectionSettings::default(),
+            objectives: Objectives::new(vec![], false),
+            stats: RunStats::new(),
+            controls: Controls::default(),
+            metadata: LevelMetadata::default(),
         }
     }
 
@@ -50,9 +77,13 @@ impl Game {
         actors.push(Actor::new_player(p_x, p_y));
 
         // Add guards
-        for ((g_x, g_y), patrol) in level_info.guard_data {
+        for (i, ((g_x, g_y), patrol)) in level_info.guard_data.into_iter().enumerate() {
             let patrol_points = patrol.iter().map(|(x, y)| Point2::new(*x, *y)).collect();
-            actors.push(Actor::new_guard(g_x, g_y, patrol_points));
+            let mut guard = Actor::new_guard(g_x, g_y, patrol_points);
+            if let Some(settings) = level_info.guard_settings.get(i) {
+                guard.apply_guard_settings(settings);
+            }
+            actors.push(guard);
         }
 
         // Make obstacles
@@ -62,19 +93,32 @@ impl Game {
             .map(|points| Polygon::new(points.iter().map(|(x, y)| Point2::new(*x, *y)).collect()))
             .collect();
 
-        // Make end area
-        let end_area = Polygon::new(
-            level_info
-                .end_area_data
-                .iter()
-                .map(|(x, y)| Point2::new(*x, *y))
-                .collect(),
-        );
+        // Make end areas
+        let end_areas = std::iter::once(&level_info.end_area_data)
+            .chain(level_info.extra_end_area_data.iter())
+            .map(|points| Polygon::new(points.iter().map(|(x, y)| Point2::new(*x, *y)).collect()))
+            .collect();
+
+        // Make light zones
+        let light_zones = level_info
+            .light_zone_data
+            .iter()
+            .map(|(light_level, points)| {
+                let polygon =
+                    Polygon::new(points.iter().map(|(x, y)| Point2::new(*x, *y)).collect());
+                LightZone::new(polygon, *light_level)
+            })
+            .collect();
 
         Game {
             actors,
-            game_map: GameMap::new(obstacles, end_area),
+            game_map: GameMap::new(obstacles, end_areas, light_zones),
             player_won: false,
+            detection: level_info.detection,
+            objectives: Objectives::new(level_info.objectives, level_info.ordered_objectives),
+            stats: RunStats::new(),
+            controls: Controls::default(),
+            metadata: level_info.metadata,
         }
     }
 
@@ -89,6 +133,7 @@ impl Game {
 
         // Get guard data
         let mut guard_data = Vec::new();
+        let mut guard_settings = Vec::new();
         self.actors
             .iter()
             .filter(|actor| !actor.is_player())
@@ -98,7 +143,8 @@ impl Game {
                     Controller::Guard(con) => con.points.verts.iter().map(|p| (p.x, p.y)).collect(),
                     _ => unreachable!(),
                 };
-                guard_data.push((pos, patrol))
+                guard_data.push((pos, patrol));
+                guard_settings.push(guard.guard_settings());
             });
 
         // Get obstacle data
@@ -109,13 +155,24 @@ impl Game {
             .map(|p| p.verts.iter().map(|v| (v.x, v.y)).collect())
             .collect();
 
-        // Get end_area_data
-        let end_area_data = self
+        // Get end area data, where the first end area is stored on its own
+        let mut end_areas = self
             .game_map
-            .end_area
-            .verts
+            .end_areas
             .iter()
-            .map(|v| (v.x, v.y))
+            .map(|p| p.verts.iter().map(|v| (v.x, v.y)).collect());
+        let end_area_data = end_areas.next().unwrap_or_default();
+        let extra_end_area_data = end_areas.collect();
+
+        // Get light zone data
+        let light_zone_data = self
+            .game_map
+            .light_zones
+            .iter()
+            .map(|zone| {
+                let points = zone.polygon.verts.iter().map(|v| (v.x, v.y)).collect();
+                (zone.light_level, points)
+            })
             .collect();
 
         LevelInfo {
@@ -123,52 +180,109 @@ impl Game {
             guard_data,
             obstacle_data,
             end_area_data,
+            extra_end_area_data,
+            light_zone_data,
+            detection: self.detection.clone(),
+            objectives: self.objectives.to_data(),
+            ordered_objectives: self.objectives.ordered,
+            guard_settings,
+            metadata: self.metadata.clone(),
         }
     }
 
-    pub fn tick(&mut self, ctx: &mut Context) -> Vec<ViewEvent> {
-        let mut events = vec![];
-
+    pub fn tick(&mut self, ctx: &mut Context) -> Option<Outcome> {
         apply_physics_movement(self, ctx);
+        self.update_objectives(ctx);
 
-        if self.player_won {
-            println!("You won!");
-            events.push(ViewEvent::PopView);
+        if let Some(player) = self.actors.iter().find(|actor| actor.is_player()) {
+            self.stats.record_movement(player.velocity);
         }
 
-        if was_player_found(self) {
-            println!("Player was discovered...");
-            events.push(ViewEvent::PopView);
-        }
+        let player_found = was_player_found(self);
+
+        let outcome = if self.player_won {
+            self.objectives.finish();
+            Some(Outcome::Won)
+        } else if player_found {
+            Some(Outcome::Discovered)
+        } else if self.objectives.has_failed() {
+            Some(Outcome::ObjectiveFailed)
+        } else {
+            None
+        };
 
         for actor in &mut self.actors {
             actor.update_fov(&self.game_map);
         }
 
-        events
+        outcome
+    }
+
+    /// Get the statistics of the run so far, including the time and objective progress
+    pub fn run_stats(&self) -> RunStats {
+        let mut stats = self.stats.clone();
+        stats.time = self.objectives.elapsed_seconds();
+        stats.objectives_total = self.objectives.list.len();
+        stats.objectives_completed = self
+            .objectives
+            .list
+            .iter()
+            .filter(|o| o.state == ObjectiveState::Completed)
+            .count();
+        stats
+    }
+
+    /// Get the highest detection meter among the guards
+    pub fn max_detection(&self) -> f32 {
+        self.actors
+            .iter()
+            .map(|actor| actor.discovered_player)
+            .fold(0.0, f32::max)
+    }
+
+    fn update_objectives(&mut self, ctx: &Context) {
+        let max_detection = self.max_detection();
+        let interacting = self.controls.is_pressed(ctx, Action::Interact);
+
+        let player = self
+            .actors
+            .iter()
+            .find(|actor| actor.is_player())
+            .expect("no player actor found");
+        self.objectives.update(player, max_detection, interacting);
     }
 }
 
 fn was_player_found(game: &mut Game) -> bool {
-    let pos = game
+    let player = game
         .actors
         .iter()
-        .filter(|actor| actor.is_player())
-        .map(|actor| actor.pos)
-        .next()
+        .find(|actor| actor.is_player())
         .expect("no player actor found");
+    let pos = player.pos;
+    let movement = player.movement();
+    let light_level = game
+        .game_map
+        .light_level_at(pos, game.detection.ambient_light);
 
+    let mut spotted = false;
     for actor in game.actors.iter_mut().filter(|actor| !actor.is_player()) {
         if actor.fov.is_inside_fov(&game.game_map, pos) {
-            actor.discovered_player += 0.015; // approx 60 ticks = 1 second to discover
+            spotted = true;
+            let distance_ratio = distance(&actor.pos, &pos) / actor.fov.view_distance();
+            actor.discovered_player +=
+                game.detection
+                    .detection_rate(light_level, distance_ratio, movement);
         } else {
-            actor.discovered_player -= 0.015;
+            actor.discovered_player -= game.detection.decay_rate;
             if actor.discovered_player < 0.0 {
                 actor.discovered_player = 0.0;
             }
         }
     }
 
+    game.stats.record_sighting(spotted);
+
     game.actors
         .iter()
         .any(|actor| actor.discovered_player >= 1.0)
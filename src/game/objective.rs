@@ -0,0 +1,230 @@
+use crate::game::{actor::Actor, polygon::Polygon};
+use nalgebra::{distance, Point2};
+use serde::{Deserialize, Serialize};
+
+type Point = (f32, f32);
+
+/// How close the player has to be to pick up an item or use a switch
+pub const INTERACT_RADIUS: f32 = 30.0;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ObjectiveKind {
+    ReachZone { area: Vec<Point> },
+    CollectItem { pos: Point },
+    ActivateSwitch { pos: Point },
+    StayUndetected { max_detection: f32 },
+    TimeLimit { seconds: f32 },
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ObjectiveData {
+    pub description: String,
+    #[serde(default = "required_by_default")]
+    pub required: bool,
+    #[serde(flatten)]
+    pub kind: ObjectiveKind,
+}
+
+fn required_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectiveState {
+    Pending,
+    Completed,
+    Failed,
+}
+
+pub struct Objective {
+    pub data: ObjectiveData,
+    pub state: ObjectiveState,
+}
+
+impl Objective {
+    pub fn new(data: ObjectiveData) -> Self {
+        Objective {
+            data,
+            state: ObjectiveState::Pending,
+        }
+    }
+
+    /// Ongoing objectives hold from the start of the level until they fail
+    pub fn is_ongoing(&self) -> bool {
+        matches!(
+            self.data.kind,
+            ObjectiveKind::StayUndetected { .. } | ObjectiveKind::TimeLimit { .. }
+        )
+    }
+
+    pub fn is_done(&self) -> bool {
+        match self.state {
+            ObjectiveState::Completed => true,
+            ObjectiveState::Failed => false,
+            ObjectiveState::Pending => self.is_ongoing(),
+        }
+    }
+}
+
+pub struct Objectives {
+    pub list: Vec<Objective>,
+    pub ordered: bool,
+    ticks: u32,
+}
+
+impl Objectives {
+    pub fn new(data: Vec<ObjectiveData>, ordered: bool) -> Self {
+        Objectives {
+            list: data.into_iter().map(Objective::new).collect(),
+            ordered,
+            ticks: 0,
+        }
+    }
+
+    pub fn to_data(&self) -> Vec<ObjectiveData> {
+        self.list.iter().map(|o| o.data.clone()).collect()
+    }
+
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.ticks as f32 / 60.0
+    }
+
+    /// Count the items that are still to be collected, and the total number of items
+    pub fn remaining_items(&self) -> (usize, usize) {
+        let items = self
+            .list
+            .iter()
+            .filter(|o| matches!(o.data.kind, ObjectiveKind::CollectItem { .. }));
+        let total = items.clone().count();
+        let remaining = items.filter(|o| o.state == ObjectiveState::Pending).count();
+        (remaining, total)
+    }
+
+    /// The exit only opens once every required objective is done
+    pub fn exit_active(&self) -> bool {
+        self.list
+            .iter()
+            .filter(|o| o.data.required)
+            .all(|o| o.is_done())
+    }
+
+    pub fn has_failed(&self) -> bool {
+        self.list
+            .iter()
+            .any(|o| o.data.required && o.state == ObjectiveState::Failed)
+    }
+
+    /// Index of the required objective that has to be completed next, if they are ordered
+    fn current_index(&self) -> Option<usize> {
+        self.list
+            .iter()
+            .position(|o| o.data.required && !o.is_ongoing() && !o.is_done())
+    }
+
+    /// Update the objectives after a tick, where `max_detection` is the highest detection
+    /// meter among the guards and `interacting` is whether the player is using the interact key
+    pub fn update(&mut self, player: &Actor, max_detection: f32, interacting: bool) {
+        self.ticks += 1;
+        let elapsed = self.elapsed_seconds();
+        let current = self.current_index();
+
+        for (i, objective) in self.list.iter_mut().enumerate() {
+            if objective.state != ObjectiveState::Pending {
+                continue;
+            }
+
+            let blocked = self.ordered && objective.data.required && Some(i) != current;
+
+            objective.state = match &objective.data.kind {
+                ObjectiveKind::ReachZone { area } if !blocked => {
+                    let polygon =
+                        Polygon::new(area.iter().map(|(x, y)| Point2::new(*x, *y)).collect());
+                    if polygon.encloses(player.pos) {
+                        ObjectiveState::Completed
+                    } else {
+                        ObjectiveState::Pending
+                    }
+                }
+                ObjectiveKind::CollectItem { pos } if !blocked => {
+                    if distance(&Point2::new(pos.0, pos.1), &player.pos) <= INTERACT_RADIUS {
+                        ObjectiveState::Completed
+                    } else {
+                        ObjectiveState::Pending
+                    }
+                }
+                ObjectiveKind::ActivateSwitch { pos } if !blocked && interacting => {
+                    if distance(&Point2::new(pos.0, pos.1), &player.pos) <= INTERACT_RADIUS {
+                        ObjectiveState::Completed
+                    } else {
+                        ObjectiveState::Pending
+                    }
+                }
+                ObjectiveKind::StayUndetected {
+                    max_detection: limit,
+                } if max_detection >= *limit => ObjectiveState::Failed,
+                ObjectiveKind::TimeLimit { seconds } if elapsed > *seconds => {
+                    ObjectiveState::Failed
+                }
+                _ => ObjectiveState::Pending,
+            };
+        }
+    }
+
+    /// Mark the ongoing objectives that held until the end as completed
+    pub fn finish(&mut self) {
+        for objective in &mut self.list {
+            if objective.is_ongoing() && objective.state == ObjectiveState::Pending {
+                objective.state = ObjectiveState::Completed;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod objective_tests {
+    use super::{Actor, ObjectiveData, ObjectiveKind, ObjectiveState, Objectives};
+
+    fn item(x: f32, y: f32) -> ObjectiveData {
+        ObjectiveData {
+            description: String::from("Collect item"),
+            required: true,
+            kind: ObjectiveKind::CollectItem { pos: (x, y) },
+        }
+    }
+
+    #[test]
+    fn ordered_objectives_complete_in_order() {
+        let mut objectives = Objectives::new(vec![item(0.0, 0.0), item(100.0, 0.0)], true);
+        let mut player = Actor::new_player(100.0, 0.0);
+
+        objectives.update(&player, 0.0, false);
+        assert_eq!(objectives.list[1].state, ObjectiveState::Pending);
+
+        player.pos.x = 0.0;
+        objectives.update(&player, 0.0, false);
+        assert_eq!(objectives.list[0].state, ObjectiveState::Completed);
+        assert!(!objectives.exit_active());
+
+        player.pos.x = 100.0;
+        objectives.update(&player, 0.0, false);
+        assert!(objectives.exit_active());
+    }
+
+    #[test]
+    fn stay_undetected_fails() {
+        let data = ObjectiveData {
+            description: String::from("Stay hidden"),
+            required: true,
+            kind: ObjectiveKind::StayUndetected { max_detection: 0.5 },
+        };
+        let mut objectives = Objectives::new(vec![data], false);
+        let player = Actor::new_player(0.0, 0.0);
+
+        objectives.update(&player, 0.2, false);
+        assert!(objectives.exit_active());
+
+        objectives.update(&player, 0.6, false);
+        assert!(objectives.has_failed());
+    }
+}
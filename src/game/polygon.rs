@@ -22,6 +22,20 @@ impl Polygon {
         self.bounding_box().contains(mouse_pos)
     }
 
+    /// Check whether the point lies inside the polygon, using the even-odd rule
+    pub fn encloses(&self, point: Point2<f32>) -> bool {
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            if (a.y > point.y) != (b.y > point.y) {
+                let intersect_x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if point.x < intersect_x {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+
     pub fn bounding_box(&self) -> Rect {
         let min_x = self
             .verts
@@ -84,3 +98,25 @@ impl Iterator for EdgeIterator {
         Some((a, b))
     }
 }
+
+#[cfg(test)]
+mod polygon_tests {
+    use super::{Point2, Polygon};
+
+    #[test]
+    fn encloses_concave() {
+        // An L-shape, where the bounding box covers the missing corner
+        let polygon = Polygon::new(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(2.0, 0.0),
+            Point2::new(2.0, 1.0),
+            Point2::new(1.0, 1.0),
+            Point2::new(1.0, 2.0),
+            Point2::new(0.0, 2.0),
+        ]);
+        assert!(polygon.encloses(Point2::new(0.5, 1.5)));
+        assert!(polygon.encloses(Point2::new(1.5, 0.5)));
+        assert!(!polygon.encloses(Point2::new(1.5, 1.5)));
+        assert!(!polygon.encloses(Point2::new(3.0, 0.5)));
+    }
+}
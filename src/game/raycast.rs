@@ -26,7 +26,7 @@ fn get_rotation_matrix(theta: f32) -> Matrix2<f32> {
 }
 
 pub fn raycast(ray: &Ray, polygons: &[Polygon], max_distance: f32) -> Option<Point2<f32>> {
-    let mut closest_point_dist = std::f32::MAX;
+    let mut closest_point_dist = f32::MAX;
     let mut closest_point = None;
 
     for polygon in polygons {
@@ -107,7 +107,7 @@ mod raycast_tests {
 
     #[test]
     fn numbers() {
-        assert!(360.0_f32.to_radians() - 2.0 * std::f32::consts::PI <= std::f32::EPSILON);
+        assert!(360.0_f32.to_radians() - 2.0 * std::f32::consts::PI <= f32::EPSILON);
     }
 
     #[test]
@@ -1,26 +1,51 @@
 use super::{
-    actor::Actor, controller::Controller, fov::FieldOfView, game_map::GameMap, polygon::Polygon,
+    actor::Actor,
+    controller::{Controller, GuardController},
+    fov::FieldOfView,
+    game_map::{GameMap, LightZone},
+    objective::{ObjectiveKind, ObjectiveState, Objectives},
+    polygon::Polygon,
     Game,
 };
 use crate::{
-    editor::{PolygonType, SelectionHandler, SelectionObject},
+    editor::{
+        draw_tool::{Shape, ShapeDrawing},
+        snapping::SnapGuide,
+        PolygonType, SelectionHandler, SelectionObject,
+    },
     view::editor::GRID_SIZE,
 };
 use ggez::{
-    graphics::{self, Canvas, Color, DrawMode, DrawParam, Mesh, Rect},
+    graphics::{
+        self, Canvas, Color, DrawMode, DrawParam, Mesh, PxScale, Quad, Rect, Text, TextFragment,
+    },
     Context, GameResult,
 };
-use nalgebra::Point2;
+use nalgebra::{distance, Point2, Vector2};
 
 pub const GRID_LINE: Color = Color::new(0.3, 0.3, 0.3, 1.0);
 pub const END_AREA: Color = Color::new(0.0, 1.0, 0.0, 0.1);
 pub const END_AREA_SELECTED: Color = Color::new(0.5, 1.0, 0.5, 0.1);
+pub const END_AREA_INACTIVE: Color = Color::new(0.5, 0.5, 0.5, 0.1);
 pub const PLAYER_VISIBLE_AREA: Color = Color::new(1.0, 1.0, 1.0, 0.1);
 pub const GUARD_VISIBLE_AREA: Color = Color::new(1.0, 0.0, 0.0, 0.1);
 pub const GUARD: Color = Color::new(0.0, 0.0, 1.0, 1.0);
 pub const GUARD_SELECTED: Color = Color::new(0.2, 0.2, 1.0, 1.0);
 pub const OBSTACLE: Color = Color::new(0.4, 0.4, 0.4, 1.0);
 pub const OBSTACLE_SELECTED: Color = Color::new(0.5, 0.5, 0.5, 1.0);
+pub const LIGHT_ZONE_SELECTED: Color = Color::new(1.0, 1.0, 1.0, 0.5);
+pub const OBJECTIVE_ZONE: Color = Color::new(0.3, 0.8, 1.0, 0.8);
+pub const OBJECTIVE_ITEM: Color = Color::new(1.0, 0.85, 0.0, 1.0);
+pub const OBJECTIVE_SWITCH: Color = Color::new(1.0, 0.3, 0.3, 1.0);
+pub const OBJECTIVE_DONE: Color = Color::new(0.3, 1.0, 0.3, 0.8);
+pub const DETECTION_METER: Color = Color::new(1.0, 0.3, 0.3, 1.0);
+pub const VERTEX: Color = Color::WHITE;
+pub const VERTEX_SELECTED: Color = Color::new(1.0, 0.85, 0.0, 1.0);
+pub const PSEUDO_VERTEX: Color = Color::new(1.0, 1.0, 1.0, 0.2);
+pub const SELECTION_BOX: Color = Color::new(0.3, 0.5, 1.0, 0.8);
+pub const DRAWING: Color = Color::new(1.0, 1.0, 1.0, 0.8);
+pub const DRAWING_INVALID: Color = Color::new(1.0, 0.3, 0.3, 0.8);
+pub const SNAP_GUIDE: Color = Color::new(0.3, 1.0, 1.0, 0.9);
 
 pub struct Renderer {}
 
@@ -41,10 +66,145 @@ impl Renderer {
         if selection_handler.is_some() {
             self.draw_grid(ctx, canvas)?;
         }
+        self.draw_light_zones(
+            ctx,
+            canvas,
+            &game.game_map,
+            game.detection.ambient_light,
+            selection_handler,
+        )?;
         self.draw_all_fov(ctx, canvas, &game.actors)?;
         self.draw_obstacles(ctx, canvas, &game.game_map, selection_handler)?;
-        self.draw_end_area(ctx, canvas, &game.game_map, selection_handler)?;
+        self.draw_end_areas(
+            ctx,
+            canvas,
+            &game.game_map,
+            game.objectives.exit_active(),
+            selection_handler,
+        )?;
+        self.draw_objective_markers(ctx, canvas, &game.objectives)?;
         self.draw_actors(ctx, canvas, &game.actors, selection_handler)?;
+        if let Some((start, end)) = selection_handler.and_then(|handler| handler.selection_box) {
+            self.draw_selection_box(ctx, canvas, start, end)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_selection_box(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        start: Point2<f32>,
+        end: Point2<f32>,
+    ) -> GameResult {
+        let bounds = Rect::new(
+            start.x.min(end.x),
+            start.y.min(end.y),
+            (end.x - start.x).abs(),
+            (end.y - start.y).abs(),
+        );
+        if bounds.w < 1.0 || bounds.h < 1.0 {
+            return Ok(());
+        }
+
+        let mut fill = SELECTION_BOX;
+        fill.a = 0.1;
+        canvas.draw(&Quad, DrawParam::default().dest_rect(bounds).color(fill));
+        let outline = Mesh::new_rectangle(ctx, DrawMode::stroke(1.0), bounds, SELECTION_BOX)?;
+        canvas.draw(&outline, DrawParam::default());
+
+        Ok(())
+    }
+
+    /// Draw the outline of a shape being drawn in the editor, red where the next click is not
+    /// allowed
+    pub fn draw_shape_drawing(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        drawing: &ShapeDrawing,
+    ) -> GameResult {
+        for vertex in drawing.points() {
+            self.draw_polygon_vertex(ctx, canvas, vertex, VERTEX)?;
+        }
+
+        let mut outline = drawing.outline();
+        // Rectangles and circles are closed as soon as their size is picked
+        if drawing.shape() != Shape::Polygon && !outline.is_empty() {
+            outline.push(outline[0]);
+        }
+        // Meshes can not be made from lines without a length
+        if !outline
+            .windows(2)
+            .any(|edge| distance(&edge[0], &edge[1]) > 1.0)
+        {
+            return Ok(());
+        }
+
+        let color = if drawing.is_valid() {
+            DRAWING
+        } else {
+            DRAWING_INVALID
+        };
+        let mesh = Mesh::new_line(ctx, &outline, 2.0, color)?;
+        canvas.draw(&mesh, DrawParam::default());
+
+        Ok(())
+    }
+
+    /// Show what a dragged point snapped to
+    pub fn draw_snap_guide(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        guide: SnapGuide,
+    ) -> GameResult {
+        let mesh = match guide {
+            SnapGuide::Vertex(vertex) => {
+                Mesh::new_circle(ctx, DrawMode::stroke(2.0), vertex, 9.0, 0.1, SNAP_GUIDE)?
+            }
+            SnapGuide::Edge(a, b) => Mesh::new_line(ctx, &[a, b], 3.0, SNAP_GUIDE)?,
+            // The dragged point may be on the previous vertex, which makes no line
+            SnapGuide::Axis(a, b) if distance(&a, &b) > 1.0 => {
+                Mesh::new_line(ctx, &[a, b], 1.0, SNAP_GUIDE)?
+            }
+            SnapGuide::Axis(..) => return Ok(()),
+        };
+        canvas.draw(&mesh, DrawParam::default());
+
+        Ok(())
+    }
+
+    /// Draw the level as it is at the start, with the patrol path of every guard, to show what
+    /// the level looks like in thumbnails
+    pub fn render_preview(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+    ) -> GameResult {
+        // Keep lines the same width in pixels however much of the level is shown
+        let line_width = canvas
+            .screen_coordinates()
+            .map_or(2.0, |coords| coords.w / 64.0);
+
+        self.draw_light_zones(
+            ctx,
+            canvas,
+            &game.game_map,
+            game.detection.ambient_light,
+            None,
+        )?;
+        self.draw_obstacles(ctx, canvas, &game.game_map, None)?;
+        self.draw_end_areas(ctx, canvas, &game.game_map, true, None)?;
+        self.draw_objective_markers(ctx, canvas, &game.objectives)?;
+        for actor in &game.actors {
+            if let Controller::Guard(guard) = &actor.controller {
+                self.draw_guard_patrol_path(ctx, canvas, guard, line_width)?;
+            }
+        }
+        self.draw_actors(ctx, canvas, &game.actors, None)?;
 
         Ok(())
     }
@@ -89,6 +249,55 @@ impl Renderer {
         Ok(())
     }
 
+    fn draw_light_zones(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game_map: &GameMap,
+        ambient_light: f32,
+        selection_handler: Option<&SelectionHandler>,
+    ) -> GameResult<()> {
+        for (i, zone) in game_map.light_zones.iter().enumerate() {
+            if zone.polygon.verts.len() < 3 {
+                continue;
+            }
+
+            let polygon_type = PolygonType::LightZone { index: i };
+            let is_selected = selection_handler.is_some_and(|handler| {
+                handler.is_selected(SelectionObject::Polygon { polygon_type })
+            });
+
+            let mesh = Mesh::new_polygon(
+                ctx,
+                graphics::DrawMode::fill(),
+                &zone.polygon.verts,
+                light_zone_color(zone, ambient_light),
+            )?;
+            canvas.draw(&mesh, graphics::DrawParam::default());
+
+            if is_selected {
+                let outline = Mesh::new_polygon(
+                    ctx,
+                    DrawMode::stroke(2.0),
+                    &zone.polygon.verts,
+                    LIGHT_ZONE_SELECTED,
+                )?;
+                canvas.draw(&outline, graphics::DrawParam::default());
+            }
+            if let Some(handler) = selection_handler {
+                self.draw_selected_polygon_vertices(
+                    ctx,
+                    canvas,
+                    handler,
+                    polygon_type,
+                    &zone.polygon,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn draw_all_fov(
         &self,
         ctx: &mut Context,
@@ -138,15 +347,10 @@ impl Renderer {
         selection_handler: Option<&SelectionHandler>,
     ) -> GameResult<()> {
         for (i, polygon) in game_map.obstacles.iter().enumerate() {
-            let is_selected = matches!(selection_handler,
-                Some(&SelectionHandler {
-                    selected_object:
-                        Some(SelectionObject::Polygon {
-                            polygon_type: PolygonType::Obstacle { index },
-                        }),
-                    ..
-                }) if index == i,
-            );
+            let polygon_type = PolygonType::Obstacle { index: i };
+            let is_selected = selection_handler.is_some_and(|handler| {
+                handler.is_selected(SelectionObject::Polygon { polygon_type })
+            });
 
             let color = if is_selected {
                 OBSTACLE_SELECTED
@@ -158,62 +362,199 @@ impl Renderer {
 
             canvas.draw(&mesh, graphics::DrawParam::default());
 
-            if is_selected {
-                self.draw_polygon_vertices(ctx, canvas, polygon)?;
+            if let Some(handler) = selection_handler {
+                self.draw_selected_polygon_vertices(ctx, canvas, handler, polygon_type, polygon)?;
             }
         }
 
         Ok(())
     }
 
-    fn draw_end_area(
+    fn draw_end_areas(
         &self,
         ctx: &mut Context,
         canvas: &mut Canvas,
         game_map: &GameMap,
+        exit_active: bool,
         selection_handler: Option<&SelectionHandler>,
     ) -> GameResult<()> {
-        let is_selected = matches!(
-            selection_handler,
-            Some(&SelectionHandler {
-                selected_object: Some(SelectionObject::Polygon {
-                    polygon_type: PolygonType::EndArea,
-                }),
-                ..
-            })
-        );
+        for (i, end_area) in game_map.end_areas.iter().enumerate() {
+            let polygon_type = PolygonType::EndArea { index: i };
+            let is_selected = selection_handler.is_some_and(|handler| {
+                handler.is_selected(SelectionObject::Polygon { polygon_type })
+            });
 
-        let color = if is_selected {
-            END_AREA_SELECTED
-        } else {
-            END_AREA
-        };
+            let color = if is_selected {
+                END_AREA_SELECTED
+            } else if exit_active {
+                END_AREA
+            } else {
+                END_AREA_INACTIVE
+            };
 
-        let mesh = Mesh::new_polygon(
-            ctx,
-            graphics::DrawMode::fill(),
-            &game_map.end_area.verts,
-            color,
-        )?;
+            let mesh = Mesh::new_polygon(ctx, graphics::DrawMode::fill(), &end_area.verts, color)?;
 
-        canvas.draw(&mesh, graphics::DrawParam::default());
+            canvas.draw(&mesh, graphics::DrawParam::default());
 
-        // Draw vertices if selected
-        if is_selected {
-            self.draw_polygon_vertices(ctx, canvas, &game_map.end_area)?;
+            if let Some(handler) = selection_handler {
+                self.draw_selected_polygon_vertices(ctx, canvas, handler, polygon_type, end_area)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw_objective_markers(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        objectives: &Objectives,
+    ) -> GameResult<()> {
+        for objective in &objectives.list {
+            let completed = objective.state == ObjectiveState::Completed;
+
+            match &objective.data.kind {
+                ObjectiveKind::ReachZone { area } if area.len() >= 3 => {
+                    let points: Vec<Point2<f32>> =
+                        area.iter().map(|(x, y)| Point2::new(*x, *y)).collect();
+                    let color = if completed {
+                        OBJECTIVE_DONE
+                    } else {
+                        OBJECTIVE_ZONE
+                    };
+                    let mesh = Mesh::new_polygon(ctx, DrawMode::stroke(2.0), &points, color)?;
+                    canvas.draw(&mesh, DrawParam::default());
+                }
+                ObjectiveKind::CollectItem { pos } if !completed => {
+                    let mesh = Mesh::new_circle(
+                        ctx,
+                        DrawMode::fill(),
+                        [pos.0, pos.1],
+                        8.0,
+                        0.5,
+                        OBJECTIVE_ITEM,
+                    )?;
+                    canvas.draw(&mesh, DrawParam::default());
+                }
+                ObjectiveKind::ActivateSwitch { pos } => {
+                    let color = if completed {
+                        OBJECTIVE_DONE
+                    } else {
+                        OBJECTIVE_SWITCH
+                    };
+                    let bounds = Rect::new(pos.0 - 8.0, pos.1 - 8.0, 16.0, 16.0);
+                    let mesh = Mesh::new_rectangle(ctx, DrawMode::fill(), bounds, color)?;
+                    canvas.draw(&mesh, DrawParam::default());
+                }
+                _ => {}
+            }
         }
 
         Ok(())
     }
 
+    /// Draw the list of objectives and their state in the top left corner
+    pub fn draw_objective_list(&self, canvas: &mut Canvas, objectives: &Objectives) {
+        let mut y = 10.0;
+        for objective in &objectives.list {
+            let mark = match objective.state {
+                ObjectiveState::Completed => "[x]",
+                ObjectiveState::Failed => "[!]",
+                ObjectiveState::Pending => "[ ]",
+            };
+            let mut line = format!("{} {}", mark, objective.data.description);
+            if !objective.data.required {
+                line.push_str(" (optional)");
+            }
+            if let ObjectiveKind::TimeLimit { seconds } = objective.data.kind {
+                let remaining = (seconds - objectives.elapsed_seconds()).max(0.0);
+                line.push_str(&format!(" - {:.0}s left", remaining.ceil()));
+            }
+
+            let text = Text::new(TextFragment::new(line).scale(PxScale::from(18.0)));
+            canvas.draw(&text, DrawParam::default().dest(Point2::new(10.0, y)));
+            y += 22.0;
+        }
+    }
+
+    /// Draw the level name, elapsed time, remaining items and the highest guard
+    /// detection meter in the top right corner
+    pub fn draw_hud(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        game: &Game,
+        level_name: &str,
+    ) -> GameResult {
+        let screen_coords = canvas.screen_coordinates().unwrap();
+        let left = screen_coords.x + screen_coords.w - 210.0;
+        let mut y = screen_coords.y + 10.0;
+
+        let mut lines = vec![
+            level_name.to_owned(),
+            format!("Time: {:.1} s", game.objectives.elapsed_seconds()),
+        ];
+        let (remaining, total) = game.objectives.remaining_items();
+        if total > 0 {
+            lines.push(format!("Items left: {}/{}", remaining, total));
+        }
+
+        for line in lines {
+            let text = Text::new(TextFragment::new(line).scale(PxScale::from(18.0)));
+            canvas.draw(&text, DrawParam::default().dest(Point2::new(left, y)));
+            y += 22.0;
+        }
+
+        // Detection meter
+        let outline = Rect::new(left, y + 4.0, 200.0, 12.0);
+        let mesh = Mesh::new_rectangle(ctx, DrawMode::stroke(1.0), outline, Color::WHITE)?;
+        canvas.draw(&mesh, DrawParam::default());
+
+        let detection = game.max_detection().clamp(0.0, 1.0);
+        if detection > 0.0 {
+            let fill = Rect::new(left, y + 4.0, 200.0 * detection, 12.0);
+            let mesh = Mesh::new_rectangle(ctx, DrawMode::fill(), fill, DETECTION_METER)?;
+            canvas.draw(&mesh, DrawParam::default());
+        }
+
+        Ok(())
+    }
+
+    /// Draw the vertices of a polygon if they can be edited
+    fn draw_selected_polygon_vertices(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        handler: &SelectionHandler,
+        polygon_type: PolygonType,
+        polygon: &Polygon,
+    ) -> GameResult {
+        if !handler.shows_vertices(polygon_type) {
+            return Ok(());
+        }
+
+        self.draw_polygon_vertices(ctx, canvas, polygon, |index| {
+            handler.is_selected(SelectionObject::PolygonVertex {
+                polygon_type,
+                index,
+            })
+        })
+    }
+
     fn draw_polygon_vertices(
         &self,
         ctx: &mut Context,
         canvas: &mut Canvas,
         polygon: &Polygon,
+        is_selected: impl Fn(usize) -> bool,
     ) -> GameResult {
-        for vertex in &polygon.verts {
-            self.draw_polygon_vertex(ctx, canvas, vertex, false)?;
+        for (index, vertex) in polygon.verts.iter().enumerate() {
+            let color = if is_selected(index) {
+                VERTEX_SELECTED
+            } else {
+                VERTEX
+            };
+            self.draw_polygon_vertex(ctx, canvas, vertex, color)?;
         }
 
         // Draw pseudovertices on all edges
@@ -221,7 +562,7 @@ impl Renderer {
             let avg_x = (start_point.x + end_point.x) / 2.0;
             let avg_y = (start_point.y + end_point.y) / 2.0;
             let middle_point = Point2::new(avg_x, avg_y);
-            self.draw_polygon_vertex(ctx, canvas, &middle_point, true)?;
+            self.draw_polygon_vertex(ctx, canvas, &middle_point, PSEUDO_VERTEX)?;
         }
 
         Ok(())
@@ -232,14 +573,8 @@ impl Renderer {
         ctx: &mut Context,
         canvas: &mut Canvas,
         vertex: &Point2<f32>,
-        pseudovertex: bool,
+        color: Color,
     ) -> GameResult {
-        let color = if pseudovertex {
-            Color::new(1.0, 1.0, 1.0, 0.2)
-        } else {
-            graphics::Color::WHITE
-        };
-
         let mesh = Mesh::new_circle(ctx, graphics::DrawMode::fill(), *vertex, 5.0, 0.01, color)?;
 
         canvas.draw(&mesh, graphics::DrawParam::default());
@@ -269,13 +604,8 @@ impl Renderer {
         actor: &Actor,
         selection_handler: Option<&SelectionHandler>,
     ) -> GameResult<()> {
-        let is_selected = matches!(
-            selection_handler,
-            Some(&SelectionHandler {
-                selected_object: Some(SelectionObject::Actor { index: i }),
-                ..
-            }) if i == index
-        );
+        let is_selected = selection_handler
+            .is_some_and(|handler| handler.is_selected(SelectionObject::Actor { index }));
         let mut color = graphics::Color::WHITE;
 
         if let Controller::Guard(guard) = &actor.controller {
@@ -288,11 +618,26 @@ impl Renderer {
             )?;
             color = GUARD;
 
+            if let Some(handler) = selection_handler.filter(|h| h.shows_patrol_path(index)) {
+                self.draw_guard_patrol_path(ctx, canvas, guard, 2.0)?;
+                self.draw_waypoint_settings(ctx, canvas, guard)?;
+                self.draw_polygon_vertices(ctx, canvas, &guard.points, |vertex_index| {
+                    handler.is_selected(SelectionObject::GuardPathVertex {
+                        actor_index: index,
+                        vertex_index,
+                    })
+                })?;
+            }
             if is_selected {
-                self.draw_guard_patrol_path(ctx, canvas, &guard.points.verts)?;
-                self.draw_polygon_vertices(ctx, canvas, &guard.points)?;
                 color = GUARD_SELECTED;
             }
+
+            // Show which way guards face at the start in the editor
+            if selection_handler.is_some() {
+                let end = actor.pos + actor.direction.into_inner() * actor.radius * 1.6;
+                let line = Mesh::new_line(ctx, &[actor.pos, end], 3.0, graphics::Color::WHITE)?;
+                canvas.draw(&line, DrawParam::default());
+            }
         }
 
         let mesh = Mesh::new_circle(
@@ -338,11 +683,64 @@ impl Renderer {
         &self,
         ctx: &mut Context,
         canvas: &mut Canvas,
-        points: &[Point2<f32>],
+        guard: &GuardController,
+        width: f32,
     ) -> GameResult {
-        let mesh = Mesh::new_polygon(ctx, DrawMode::stroke(2.0), points, graphics::Color::WHITE)?;
+        let points = &guard.points.verts;
+        // Paths with less than three points are not closed polygons, and neither are paths that
+        // are walked back or only once
+        let mesh = match points.len() {
+            0 | 1 => return Ok(()),
+            2 => Mesh::new_line(ctx, points, width, graphics::Color::WHITE)?,
+            _ if !guard.mode.is_closed() => {
+                Mesh::new_line(ctx, points, width, graphics::Color::WHITE)?
+            }
+            _ => Mesh::new_polygon(ctx, DrawMode::stroke(width), points, graphics::Color::WHITE)?,
+        };
         canvas.draw(&mesh, DrawParam::default());
 
         Ok(())
     }
+
+    /// Ring the waypoints where the guard waits, with lines towards where it looks
+    fn draw_waypoint_settings(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        guard: &GuardController,
+    ) -> GameResult {
+        for (point, settings) in guard.points.verts.iter().zip(&guard.waypoints) {
+            if settings.wait <= 0.0 {
+                continue;
+            }
+            let ring = Mesh::new_circle(
+                ctx,
+                DrawMode::stroke(2.0),
+                *point,
+                12.0,
+                0.5,
+                graphics::Color::WHITE,
+            )?;
+            canvas.draw(&ring, DrawParam::default());
+
+            for look in &settings.looks {
+                let angle = look.to_radians();
+                let end = point + Vector2::new(angle.cos(), angle.sin()) * 24.0;
+                let line = Mesh::new_line(ctx, &[*point, end], 2.0, graphics::Color::WHITE)?;
+                canvas.draw(&line, DrawParam::default());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Shade zones darker than the ambient light black, and brighter ones yellow
+fn light_zone_color(zone: &LightZone, ambient_light: f32) -> Color {
+    let contrast = zone.light_level - ambient_light;
+    if contrast < 0.0 {
+        Color::new(0.0, 0.0, 0.0, -0.6 * contrast)
+    } else {
+        Color::new(1.0, 1.0, 0.6, 0.2 * contrast)
+    }
 }
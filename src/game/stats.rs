@@ -0,0 +1,59 @@
+use nalgebra::Vector2;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Overall grade of a completed run, from worst to best
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Rank {
+    C,
+    B,
+    A,
+    S,
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Statistics collected over a single attempt at a level
+#[derive(Clone, Default)]
+pub struct RunStats {
+    /// Seconds since the start, taken from the clock of the objectives
+    pub time: f32,
+    pub times_spotted: u32,
+    pub distance_travelled: f32,
+    pub objectives_completed: usize,
+    pub objectives_total: usize,
+    currently_spotted: bool,
+}
+
+impl RunStats {
+    pub fn new() -> Self {
+        RunStats::default()
+    }
+
+    pub fn record_movement(&mut self, player_velocity: Vector2<f32>) {
+        self.distance_travelled += player_velocity.magnitude();
+    }
+
+    /// Grade the run by how often the player was spotted and how many objectives were completed
+    pub fn rank(&self) -> Rank {
+        let all_objectives = self.objectives_completed == self.objectives_total;
+        match self.times_spotted {
+            0 if all_objectives => Rank::S,
+            0..=1 => Rank::A,
+            2..=3 => Rank::B,
+            _ => Rank::C,
+        }
+    }
+
+    /// Record whether any guard can see the player, counting each new sighting once
+    pub fn record_sighting(&mut self, spotted: bool) {
+        if spotted && !self.currently_spotted {
+            self.times_spotted += 1;
+        }
+        self.currently_spotted = spotted;
+    }
+}
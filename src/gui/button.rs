@@ -1,4 +1,8 @@
-use super::{label::Label, ui_layer::UiElement};
+use super::{
+    label::Label,
+    theme,
+    ui_layer::{ElementState, UiElement},
+};
 use ggez::{
     event::MouseButton,
     graphics::{Canvas, Color, DrawMode, DrawParam, Drawable, Mesh, Rect},
@@ -10,8 +14,10 @@ pub type ButtonClickHandler<T> = dyn Fn(&mut Context) -> Option<T>;
 
 pub struct Button<T> {
     mesh: Mesh,
+    fill_mesh: Mesh,
     label: Option<Label<T>>,
     handle_click: Box<ButtonClickHandler<T>>,
+    disabled: bool,
 }
 
 impl<T> Button<T> {
@@ -21,15 +27,22 @@ impl<T> Button<T> {
         button_text: Option<&str>,
         on_click: Box<ButtonClickHandler<T>>,
     ) -> GameResult<Self> {
-        let mesh = Mesh::new_rectangle(ctx, DrawMode::stroke(3.0), bounds, Color::WHITE)?;
+        let theme = theme::current();
+        let mesh = Mesh::new_rectangle(
+            ctx,
+            DrawMode::stroke(theme.border_width),
+            bounds,
+            Color::WHITE,
+        )?;
+        let fill_mesh = Mesh::new_rectangle(ctx, DrawMode::fill(), bounds, Color::WHITE)?;
 
         let label = match button_text {
             Some(text) => {
                 let text_bounds = Rect::new(
-                    bounds.x + 5.0,
-                    bounds.y + 5.0,
-                    bounds.w - 10.0,
-                    bounds.h - 10.0,
+                    bounds.x + theme.padding,
+                    bounds.y + theme.padding,
+                    bounds.w - 2.0 * theme.padding,
+                    bounds.h - 2.0 * theme.padding,
                 );
                 Some(Label::new(ctx, text, text_bounds))
             }
@@ -38,18 +51,50 @@ impl<T> Button<T> {
 
         Ok(Button {
             mesh,
+            fill_mesh,
             label,
             handle_click: on_click,
+            disabled: false,
         })
     }
+
+    /// Disabled buttons are greyed out, can not be focused and ignore clicks
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
 }
 
 impl<T> UiElement<T> for Button<T> {
-    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
-        canvas.draw(&self.mesh, DrawParam::new().color(Color::WHITE));
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, state: ElementState) -> GameResult {
+        let theme = theme::current();
+        if self.disabled {
+            canvas.draw(&self.mesh, DrawParam::new().color(theme.disabled));
+            if let Some(label) = &self.label {
+                label.draw_with_color(canvas, theme.disabled);
+            }
+            return Ok(());
+        }
+
+        if state.pressed {
+            canvas.draw(&self.fill_mesh, DrawParam::new().color(theme.pressed_fill));
+        } else if state.hovered {
+            canvas.draw(&self.fill_mesh, DrawParam::new().color(theme.hover_fill));
+        }
+
+        let color = if state.focused {
+            theme.focused
+        } else {
+            theme.border
+        };
+        canvas.draw(&self.mesh, DrawParam::new().color(color));
 
         match &self.label {
-            Some(label) => label.draw(ctx, canvas),
+            Some(label) if state.focused => {
+                label.draw_with_color(canvas, theme.focused);
+                Ok(())
+            }
+            Some(label) => label.draw(ctx, canvas, state),
             None => Ok(()),
         }
     }
@@ -61,10 +106,14 @@ impl<T> UiElement<T> for Button<T> {
         }
     }
 
-    fn on_click(&self, ctx: &mut Context, button: MouseButton) -> Option<T> {
+    fn on_click(&mut self, ctx: &mut Context, button: MouseButton) -> Option<T> {
         match button {
-            MouseButton::Left => (self.handle_click)(ctx),
+            MouseButton::Left if !self.disabled => (self.handle_click)(ctx),
             _ => None,
         }
     }
+
+    fn is_focusable(&self) -> bool {
+        !self.disabled
+    }
 }
@@ -0,0 +1,113 @@
+use super::{
+    label::Label,
+    theme,
+    ui_layer::{ElementState, UiElement},
+};
+use ggez::{
+    event::MouseButton,
+    graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Rect},
+    Context, GameResult,
+};
+use nalgebra::Point2;
+
+pub type ToggleHandler<T> = dyn Fn(bool) -> Option<T>;
+
+/// A box that is toggled on and off, with a label to the right of it
+pub struct Checkbox<T> {
+    bounds: Rect,
+    box_mesh: Mesh,
+    check_mesh: Mesh,
+    fill_mesh: Mesh,
+    label: Label<T>,
+    checked: bool,
+    handle_toggle: Box<ToggleHandler<T>>,
+}
+
+impl<T> Checkbox<T> {
+    pub fn new(
+        ctx: &mut Context,
+        bounds: Rect,
+        text: &str,
+        checked: bool,
+        on_toggle: Box<ToggleHandler<T>>,
+    ) -> GameResult<Self> {
+        let theme = theme::current();
+        let box_bounds = Rect::new(bounds.x, bounds.y, bounds.h, bounds.h);
+        let box_mesh = Mesh::new_rectangle(
+            ctx,
+            DrawMode::stroke(theme.border_width),
+            box_bounds,
+            Color::WHITE,
+        )?;
+        let fill_mesh = Mesh::new_rectangle(ctx, DrawMode::fill(), box_bounds, Color::WHITE)?;
+
+        let inset = bounds.h * 0.25;
+        let check_mesh = Mesh::new_line(
+            ctx,
+            &[
+                Point2::new(bounds.x + inset, bounds.y + bounds.h * 0.5),
+                Point2::new(bounds.x + bounds.h * 0.45, bounds.y + bounds.h - inset),
+                Point2::new(bounds.x + bounds.h - inset, bounds.y + inset),
+            ],
+            theme.border_width,
+            Color::WHITE,
+        )?;
+
+        let label_bounds = Rect::new(
+            bounds.x + bounds.h + 10.0,
+            bounds.y,
+            bounds.w - bounds.h - 10.0,
+            bounds.h,
+        );
+
+        Ok(Checkbox {
+            bounds,
+            box_mesh,
+            check_mesh,
+            fill_mesh,
+            label: Label::new(ctx, text, label_bounds),
+            checked,
+            handle_toggle: on_toggle,
+        })
+    }
+}
+
+impl<T> UiElement<T> for Checkbox<T> {
+    fn draw(&self, _ctx: &mut Context, canvas: &mut Canvas, state: ElementState) -> GameResult {
+        let theme = theme::current();
+        if state.hovered {
+            canvas.draw(&self.fill_mesh, DrawParam::new().color(theme.hover_fill));
+        }
+
+        let color = if state.focused {
+            theme.focused
+        } else {
+            theme.text
+        };
+        canvas.draw(&self.box_mesh, DrawParam::new().color(color));
+        if self.checked {
+            canvas.draw(&self.check_mesh, DrawParam::new().color(color));
+        }
+        self.label.draw_with_color(canvas, color);
+
+        Ok(())
+    }
+
+    fn contains_point(&self, _ctx: &mut Context, point: &Point2<f32>) -> bool {
+        self.bounds.contains(*point)
+    }
+
+    fn on_click(&mut self, _ctx: &mut Context, button: MouseButton) -> Option<T> {
+        match button {
+            MouseButton::Left => {
+                self.checked = !self.checked;
+                (self.handle_toggle)(self.checked)
+            }
+            _ => None,
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+}
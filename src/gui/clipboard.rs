@@ -0,0 +1,35 @@
+use arboard::Clipboard;
+use std::sync::{Mutex, OnceLock};
+
+/// The system clipboard, opened on first use and kept open, since on some systems copied text
+/// is only available while the clipboard that copied it is open
+static CLIPBOARD: OnceLock<Option<Mutex<Clipboard>>> = OnceLock::new();
+
+/// Used when there is no system clipboard, so copying and pasting still works inside the game
+static FALLBACK: Mutex<String> = Mutex::new(String::new());
+
+fn system_clipboard() -> Option<&'static Mutex<Clipboard>> {
+    CLIPBOARD
+        .get_or_init(|| Clipboard::new().ok().map(Mutex::new))
+        .as_ref()
+}
+
+/// Get the text on the clipboard
+pub fn get_text() -> String {
+    match system_clipboard() {
+        Some(clipboard) => clipboard.lock().unwrap().get_text().unwrap_or_default(),
+        None => FALLBACK.lock().unwrap().clone(),
+    }
+}
+
+/// Put the text on the clipboard
+pub fn set_text(text: &str) {
+    match system_clipboard() {
+        Some(clipboard) => {
+            if let Err(e) = clipboard.lock().unwrap().set_text(text) {
+                eprintln!("Could not copy to the clipboard: {}", e);
+            }
+        }
+        None => *FALLBACK.lock().unwrap() = text.to_string(),
+    }
+}
@@ -0,0 +1,211 @@
+use super::{
+    theme,
+    ui_layer::{ElementState, KeyResult, UiElement},
+};
+use ggez::{
+    event::MouseButton,
+    graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Quad, Rect},
+    input::keyboard::KeyCode,
+    Context, GameResult,
+};
+use nalgebra::Point2;
+
+pub type SelectHandler<T> = dyn Fn(usize) -> Option<T>;
+
+/// Picks one of several options from a list that opens below it. With the keyboard or a
+/// gamepad, left and right cycle through the options without opening the list.
+/// It should be added after the elements below it, so the open list is drawn on top.
+pub struct Dropdown<T> {
+    bounds: Rect,
+    mesh: Mesh,
+    options: Vec<String>,
+    selected: usize,
+    open: bool,
+    /// The option under the mouse or chosen with the arrow keys while the list is open
+    highlighted: usize,
+    /// Set by a mouse press, so `on_click` knows it was not a keyboard activation
+    mouse_handled: bool,
+    handle_select: Box<SelectHandler<T>>,
+}
+
+impl<T> Dropdown<T> {
+    pub fn new(
+        ctx: &mut Context,
+        bounds: Rect,
+        options: Vec<String>,
+        selected: usize,
+        on_select: Box<SelectHandler<T>>,
+    ) -> GameResult<Self> {
+        let mesh = Mesh::new_rectangle(
+            ctx,
+            DrawMode::stroke(theme::current().border_width),
+            bounds,
+            Color::WHITE,
+        )?;
+
+        Ok(Dropdown {
+            bounds,
+            mesh,
+            selected: selected.min(options.len().saturating_sub(1)),
+            options,
+            open: false,
+            highlighted: selected,
+            mouse_handled: false,
+            handle_select: on_select,
+        })
+    }
+
+    fn option_bounds(&self, index: usize) -> Rect {
+        Rect::new(
+            self.bounds.x,
+            self.bounds.y + (index + 1) as f32 * self.bounds.h,
+            self.bounds.w,
+            self.bounds.h,
+        )
+    }
+
+    fn option_at(&self, point: &Point2<f32>) -> Option<usize> {
+        (0..self.options.len()).find(|&i| self.option_bounds(i).contains(*point))
+    }
+
+    fn select(&mut self, index: usize) -> Option<T> {
+        self.open = false;
+        if index == self.selected {
+            return None;
+        }
+        self.selected = index;
+        (self.handle_select)(index)
+    }
+
+    fn draw_text(&self, canvas: &mut Canvas, text: &str, bounds: Rect, color: Color) {
+        let padding = theme::current().padding;
+        let text = theme::current().text(text, bounds.h - 2.0 * padding);
+        let dest = Point2::new(bounds.x + padding, bounds.y + padding);
+        canvas.draw(&text, DrawParam::new().dest(dest).color(color));
+    }
+}
+
+impl<T> UiElement<T> for Dropdown<T> {
+    fn draw(&self, _ctx: &mut Context, canvas: &mut Canvas, state: ElementState) -> GameResult {
+        let theme = theme::current();
+        let color = if state.focused {
+            theme.focused
+        } else {
+            theme.border
+        };
+        canvas.draw(&self.mesh, DrawParam::new().color(color));
+
+        let selected = self.options.get(self.selected).map_or("", String::as_str);
+        let marker = if self.open { "^" } else { "v" };
+        self.draw_text(
+            canvas,
+            &format!("{} {}", marker, selected),
+            self.bounds,
+            color,
+        );
+
+        if self.open {
+            for (i, option) in self.options.iter().enumerate() {
+                let bounds = self.option_bounds(i);
+                canvas.draw(
+                    &Quad,
+                    DrawParam::new().dest_rect(bounds).color(theme.background),
+                );
+                if i == self.highlighted {
+                    canvas.draw(
+                        &Quad,
+                        DrawParam::new().dest_rect(bounds).color(theme.hover_fill),
+                    );
+                }
+                self.draw_text(canvas, option, bounds, theme.text);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn contains_point(&self, _ctx: &mut Context, point: &Point2<f32>) -> bool {
+        self.bounds.contains(*point) || (self.open && self.option_at(point).is_some())
+    }
+
+    fn on_click(&mut self, _ctx: &mut Context, _button: MouseButton) -> Option<T> {
+        if std::mem::take(&mut self.mouse_handled) {
+            return None;
+        }
+
+        // Activated with the keyboard or a gamepad
+        if self.open {
+            self.select(self.highlighted)
+        } else {
+            self.open = true;
+            self.highlighted = self.selected;
+            None
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn is_adjustable(&self) -> bool {
+        true
+    }
+
+    fn on_press(&mut self, _ctx: &mut Context, point: &Point2<f32>) {
+        self.mouse_handled = true;
+        if !self.open {
+            self.open = true;
+            self.highlighted = self.selected;
+        } else if let Some(i) = self.option_at(point) {
+            self.highlighted = i;
+        } else {
+            self.open = false;
+        }
+    }
+
+    fn on_drag(&mut self, _ctx: &mut Context, point: &Point2<f32>) -> Option<T> {
+        if let Some(i) = self.option_at(point).filter(|_| self.open) {
+            self.highlighted = i;
+        }
+        None
+    }
+
+    /// The option is picked when the mouse is released over it
+    fn on_release(&mut self, _ctx: &mut Context, point: &Point2<f32>) -> Option<T> {
+        match self.option_at(point).filter(|_| self.open) {
+            Some(i) => self.select(i),
+            None => None,
+        }
+    }
+
+    fn on_blur(&mut self) {
+        self.open = false;
+    }
+
+    fn on_key(&mut self, _ctx: &mut Context, key_code: KeyCode) -> KeyResult<T> {
+        if !self.open {
+            return KeyResult::Ignored;
+        }
+
+        match key_code {
+            KeyCode::Up => self.highlighted = self.highlighted.saturating_sub(1),
+            KeyCode::Down => self.highlighted = (self.highlighted + 1).min(self.options.len() - 1),
+            KeyCode::Escape => self.open = false,
+            _ => return KeyResult::Ignored,
+        }
+        KeyResult::Handled(None)
+    }
+
+    fn on_adjust(&mut self, _ctx: &mut Context, increase: bool) -> Option<T> {
+        if self.options.is_empty() {
+            return None;
+        }
+        let count = self.options.len();
+        let next = if increase {
+            (self.selected + 1) % count
+        } else {
+            (self.selected + count - 1) % count
+        };
+        self.select(next)
+    }
+}
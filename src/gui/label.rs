@@ -1,9 +1,9 @@
 use std::marker::PhantomData;
 
-use super::UiElement;
+use super::{layout::dpi_scale, theme, ui_layer::ElementState, UiElement};
 use ggez::{
     event::MouseButton,
-    graphics::{Canvas, DrawParam, Drawable, PxScale, Rect, Text, TextFragment},
+    graphics::{Canvas, Color, DrawParam, Drawable, PxScale, Rect, Text},
     Context, GameResult,
 };
 use nalgebra::Point2;
@@ -11,13 +11,16 @@ use nalgebra::Point2;
 pub struct Label<T> {
     text: Text,
     dest: Point2<f32>,
+    /// The text is rendered at the size in physical pixels and scaled down when drawn,
+    /// so it stays sharp on high DPI screens
+    dpi_scale: f32,
     phantom: PhantomData<T>,
 }
 
 impl<T> Label<T> {
     pub fn new(ctx: &mut Context, label_text: &str, bounds: Rect) -> Self {
         let dest = Point2::new(bounds.x, bounds.y);
-        let mut text = Text::new(TextFragment::new(label_text));
+        let mut text = theme::current().text(label_text, 16.0);
 
         // Find out the maximal size of the text inside the bounds
         let text_dim = text.dimensions(ctx).unwrap();
@@ -31,19 +34,31 @@ impl<T> Label<T> {
         };
 
         // Set the text size
-        text.set_scale(font_scale);
+        let dpi_scale = dpi_scale(ctx);
+        text.set_scale(PxScale::from(font_scale.y * dpi_scale));
 
         Label {
             text,
             dest,
+            dpi_scale,
             phantom: PhantomData,
         }
     }
+
+    pub fn draw_with_color(&self, canvas: &mut Canvas, color: Color) {
+        canvas.draw(
+            &self.text,
+            DrawParam::default()
+                .dest(self.dest)
+                .scale([1.0 / self.dpi_scale, 1.0 / self.dpi_scale])
+                .color(color),
+        );
+    }
 }
 
 impl<T> UiElement<T> for Label<T> {
-    fn draw(&self, _ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
-        canvas.draw(&self.text, DrawParam::default().dest(self.dest));
+    fn draw(&self, _ctx: &mut Context, canvas: &mut Canvas, _state: ElementState) -> GameResult {
+        self.draw_with_color(canvas, theme::current().text);
 
         Ok(())
     }
@@ -52,7 +67,7 @@ impl<T> UiElement<T> for Label<T> {
         false
     }
 
-    fn on_click(&self, _ctx: &mut Context, _button: MouseButton) -> Option<T> {
+    fn on_click(&mut self, _ctx: &mut Context, _button: MouseButton) -> Option<T> {
         None::<T>
     }
 }
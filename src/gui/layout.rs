@@ -0,0 +1,139 @@
+use ggez::{graphics::Rect, Context};
+
+/// How many physical pixels there are per logical pixel, e.g. 2.0 on high DPI screens
+pub fn dpi_scale(ctx: &Context) -> f32 {
+    ctx.gfx.window().scale_factor() as f32
+}
+
+/// Hands out rectangles one below the other, spanning the width of the bounds
+pub struct VerticalStack {
+    bounds: Rect,
+    spacing: f32,
+    y: f32,
+}
+
+impl VerticalStack {
+    pub fn new(bounds: Rect, spacing: f32) -> Self {
+        VerticalStack {
+            bounds,
+            spacing,
+            y: bounds.y,
+        }
+    }
+
+    /// Take the next `height` pixels of the stack
+    pub fn next(&mut self, height: f32) -> Rect {
+        let rect = Rect::new(self.bounds.x, self.y, self.bounds.w, height);
+        self.y += height + self.spacing;
+        rect
+    }
+
+    /// Leave an empty gap in the stack
+    pub fn skip(&mut self, height: f32) {
+        self.y += height;
+    }
+
+    /// How many pixels are left at the bottom of the stack
+    pub fn remaining(&self) -> f32 {
+        (self.bounds.y + self.bounds.h - self.y).max(0.0)
+    }
+}
+
+/// Hands out rectangles from left to right, spanning the height of the bounds
+pub struct HorizontalStack {
+    bounds: Rect,
+    spacing: f32,
+    x: f32,
+}
+
+impl HorizontalStack {
+    pub fn new(bounds: Rect, spacing: f32) -> Self {
+        HorizontalStack {
+            bounds,
+            spacing,
+            x: bounds.x,
+        }
+    }
+
+    /// Take the next `width` pixels of the stack
+    pub fn next(&mut self, width: f32) -> Rect {
+        let rect = Rect::new(self.x, self.bounds.y, width, self.bounds.h);
+        self.x += width + self.spacing;
+        rect
+    }
+
+    /// Split the bounds into `count` columns of equal width
+    pub fn split(bounds: Rect, count: usize, spacing: f32) -> Vec<Rect> {
+        let width = (bounds.w - spacing * (count as f32 - 1.0)) / count as f32;
+        let mut stack = HorizontalStack::new(bounds, spacing);
+        (0..count).map(|_| stack.next(width)).collect()
+    }
+}
+
+/// Where a rectangle is placed inside its container
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Center,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Place a `width` by `height` rectangle in the container, `margin` pixels from the edges
+    pub fn place(self, container: Rect, width: f32, height: f32, margin: f32) -> Rect {
+        let left = container.x + margin;
+        let center_x = container.x + (container.w - width) / 2.0;
+        let right = container.x + container.w - width - margin;
+        let top = container.y + margin;
+        let center_y = container.y + (container.h - height) / 2.0;
+        let bottom = container.y + container.h - height - margin;
+
+        let (x, y) = match self {
+            Anchor::TopLeft => (left, top),
+            Anchor::Top => (center_x, top),
+            Anchor::TopRight => (right, top),
+            Anchor::Center => (center_x, center_y),
+            Anchor::BottomLeft => (left, bottom),
+            Anchor::Bottom => (center_x, bottom),
+            Anchor::BottomRight => (right, bottom),
+        };
+        Rect::new(x, y, width, height)
+    }
+}
+
+#[cfg(test)]
+mod layout_tests {
+    use super::{Anchor, HorizontalStack, Rect, VerticalStack};
+
+    #[test]
+    fn vertical_stack_adds_spacing() {
+        let mut stack = VerticalStack::new(Rect::new(10.0, 20.0, 100.0, 200.0), 5.0);
+        assert_eq!(stack.next(30.0), Rect::new(10.0, 20.0, 100.0, 30.0));
+        assert_eq!(stack.next(40.0), Rect::new(10.0, 55.0, 100.0, 40.0));
+        stack.skip(15.0);
+        assert_eq!(stack.next(10.0), Rect::new(10.0, 115.0, 100.0, 10.0));
+        assert_eq!(stack.remaining(), 90.0);
+    }
+
+    #[test]
+    fn horizontal_split_fills_bounds() {
+        let columns = HorizontalStack::split(Rect::new(0.0, 0.0, 100.0, 50.0), 2, 10.0);
+        assert_eq!(columns[0], Rect::new(0.0, 0.0, 45.0, 50.0));
+        assert_eq!(columns[1], Rect::new(55.0, 0.0, 45.0, 50.0));
+    }
+
+    #[test]
+    fn anchor_places_in_corners() {
+        let screen = Rect::new(0.0, 0.0, 800.0, 600.0);
+        let rect = Anchor::BottomRight.place(screen, 150.0, 30.0, 10.0);
+        assert_eq!(rect, Rect::new(640.0, 560.0, 150.0, 30.0));
+        let rect = Anchor::BottomLeft.place(screen, 150.0, 30.0, 10.0);
+        assert_eq!(rect, Rect::new(10.0, 560.0, 150.0, 30.0));
+        let rect = Anchor::Center.place(screen, 200.0, 100.0, 0.0);
+        assert_eq!(rect, Rect::new(300.0, 250.0, 200.0, 100.0));
+    }
+}
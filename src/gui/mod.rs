@@ -1,5 +1,15 @@
 pub mod button;
+pub mod checkbox;
+pub mod clipboard;
+pub mod dropdown;
 pub mod label;
+pub mod layout;
+pub mod scroll_list;
+pub mod slider;
+pub mod stepper;
+pub mod text_field;
+pub mod theme;
+pub mod toast;
 mod ui_layer;
 
 pub use ui_layer::{UiElement, UiLayer};
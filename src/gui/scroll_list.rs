@@ -0,0 +1,403 @@
+use super::{
+    layout::dpi_scale,
+    theme,
+    ui_layer::{ElementState, KeyResult, UiElement},
+};
+use ggez::{
+    event::MouseButton,
+    graphics::{Canvas, Color, DrawMode, DrawParam, Image, Mesh, Quad, Rect},
+    input::keyboard::KeyCode,
+    Context, GameResult,
+};
+use nalgebra::Point2;
+
+pub type ItemSelectHandler<T> = dyn Fn(&mut Context, usize) -> Option<T>;
+
+const SCROLLBAR_WIDTH: f32 = 6.0;
+/// How far one step of the mouse wheel scrolls
+const WHEEL_STEP: f32 = 40.0;
+/// How far the mouse has to move before a press turns into a drag instead of a click
+const DRAG_THRESHOLD: f32 = 5.0;
+
+/// One row of a `ScrollList`
+pub struct ListItem {
+    title: String,
+    details: String,
+    description: String,
+    thumbnail: Option<Image>,
+    disabled: bool,
+}
+
+impl ListItem {
+    pub fn new(title: &str) -> Self {
+        ListItem {
+            title: title.to_string(),
+            details: String::new(),
+            description: String::new(),
+            thumbnail: None,
+            disabled: false,
+        }
+    }
+
+    /// A smaller second line below the title
+    pub fn details(mut self, details: &str) -> Self {
+        self.details = details.to_string();
+        self
+    }
+
+    /// A third line below the details, which makes the other lines smaller
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    /// An image shown in a square on the left of the row
+    pub fn thumbnail(mut self, thumbnail: Image) -> Self {
+        self.thumbnail = Some(thumbnail);
+        self
+    }
+
+    /// Disabled items are greyed out and can not be picked
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+}
+
+struct Drag {
+    start_y: f32,
+    start_scroll: f32,
+    /// Whether the mouse moved far enough to scroll, so releasing it does not pick an item
+    scrolling: bool,
+}
+
+/// A list of items that scrolls with the mouse wheel, by dragging, or with the arrow keys,
+/// drawing only the part inside its bounds
+pub struct ScrollList<T> {
+    bounds: Rect,
+    mesh: Mesh,
+    items: Vec<ListItem>,
+    item_height: f32,
+    /// How many pixels the items are moved up
+    scroll: f32,
+    /// The item chosen with the keyboard or pressed with the mouse
+    highlighted: usize,
+    drag: Option<Drag>,
+    /// Set by a mouse press, so `on_click` knows it was not a keyboard activation
+    mouse_handled: bool,
+    handle_select: Box<ItemSelectHandler<T>>,
+}
+
+impl<T> ScrollList<T> {
+    pub fn new(
+        ctx: &mut Context,
+        bounds: Rect,
+        item_height: f32,
+        items: Vec<ListItem>,
+        on_select: Box<ItemSelectHandler<T>>,
+    ) -> GameResult<Self> {
+        let mesh = Mesh::new_rectangle(
+            ctx,
+            DrawMode::stroke(theme::current().border_width),
+            bounds,
+            Color::WHITE,
+        )?;
+
+        Ok(ScrollList {
+            bounds,
+            mesh,
+            items,
+            item_height,
+            scroll: 0.0,
+            highlighted: 0,
+            drag: None,
+            mouse_handled: false,
+            handle_select: on_select,
+        })
+    }
+
+    /// Highlight an item and scroll so it is visible
+    pub fn highlight(mut self, index: usize) -> Self {
+        self.highlight_item(index);
+        self
+    }
+
+    fn highlight_item(&mut self, index: usize) {
+        if self.items.is_empty() {
+            return;
+        }
+        self.highlighted = index.min(self.items.len() - 1);
+        let top = self.highlighted as f32 * self.item_height;
+        self.scroll = scroll_to_show(self.scroll, top, self.item_height, self.bounds.h);
+    }
+
+    fn set_scroll(&mut self, scroll: f32) {
+        let content_height = self.items.len() as f32 * self.item_height;
+        self.scroll = scroll.clamp(0.0, max_scroll(content_height, self.bounds.h));
+    }
+
+    fn item_bounds(&self, index: usize) -> Rect {
+        Rect::new(
+            self.bounds.x,
+            self.bounds.y + index as f32 * self.item_height - self.scroll,
+            self.bounds.w,
+            self.item_height,
+        )
+    }
+
+    fn item_at(&self, point: &Point2<f32>) -> Option<usize> {
+        if !self.bounds.contains(*point) {
+            return None;
+        }
+        let index = ((point.y - self.bounds.y + self.scroll) / self.item_height) as usize;
+        (index < self.items.len()).then_some(index)
+    }
+
+    fn select(&mut self, ctx: &mut Context, index: usize) -> Option<T> {
+        match self.items.get(index) {
+            Some(item) if !item.disabled => (self.handle_select)(ctx, index),
+            _ => None,
+        }
+    }
+
+    /// Only draw inside the bounds, the scissor rectangle is in physical pixels
+    fn clip(&self, ctx: &Context, canvas: &mut Canvas) -> GameResult {
+        let scale = dpi_scale(ctx);
+        let x = self.bounds.x.max(0.0);
+        let y = self.bounds.y.max(0.0);
+        let w = self.bounds.x + self.bounds.w - x;
+        let h = self.bounds.y + self.bounds.h - y;
+        canvas.set_scissor_rect(Rect::new(x * scale, y * scale, w * scale, h * scale))
+    }
+
+    fn draw_item(&self, canvas: &mut Canvas, index: usize, focused: bool) {
+        let item = &self.items[index];
+        let bounds = self.item_bounds(index);
+
+        let theme = theme::current();
+        let padding = theme.padding;
+        if focused && index == self.highlighted {
+            canvas.draw(
+                &Quad,
+                DrawParam::new().dest_rect(bounds).color(theme.hover_fill),
+            );
+        }
+
+        let mut text_x = bounds.x + padding;
+        if let Some(thumbnail) = &item.thumbnail {
+            let size = bounds.h - 2.0 * padding;
+            let scale = size / thumbnail.width().max(thumbnail.height()) as f32;
+            canvas.draw(
+                thumbnail,
+                DrawParam::new()
+                    .dest([text_x, bounds.y + padding])
+                    .scale([scale, scale]),
+            );
+            text_x += size + padding;
+        }
+
+        let (title_color, details_color) = if item.disabled {
+            (theme.disabled, theme.disabled)
+        } else {
+            (theme.text, theme.secondary_text)
+        };
+
+        let lines = if item.description.is_empty() {
+            2.0
+        } else {
+            3.0
+        };
+        let line_height = (bounds.h - 2.0 * padding) / lines;
+        let title = theme.text(&item.title, line_height);
+        canvas.draw(
+            &title,
+            DrawParam::new()
+                .dest([text_x, bounds.y + padding])
+                .color(title_color),
+        );
+
+        let details = theme.text(&item.details, line_height * 0.8);
+        canvas.draw(
+            &details,
+            DrawParam::new()
+                .dest([text_x, bounds.y + padding + line_height * 1.1])
+                .color(details_color),
+        );
+
+        if !item.description.is_empty() {
+            let description = theme.text(&item.description, line_height * 0.8);
+            canvas.draw(
+                &description,
+                DrawParam::new()
+                    .dest([text_x, bounds.y + padding + line_height * 2.0])
+                    .color(details_color),
+            );
+        }
+    }
+
+    fn draw_scrollbar(&self, canvas: &mut Canvas) {
+        let content_height = self.items.len() as f32 * self.item_height;
+        if content_height <= self.bounds.h {
+            return;
+        }
+
+        let height = self.bounds.h * self.bounds.h / content_height;
+        let y = self.bounds.y
+            + (self.bounds.h - height) * self.scroll / (content_height - self.bounds.h);
+        let bounds = Rect::new(
+            self.bounds.x + self.bounds.w - SCROLLBAR_WIDTH - 3.0,
+            y,
+            SCROLLBAR_WIDTH,
+            height,
+        );
+        canvas.draw(
+            &Quad,
+            DrawParam::new()
+                .dest_rect(bounds)
+                .color(theme::current().secondary_text),
+        );
+    }
+}
+
+impl<T> UiElement<T> for ScrollList<T> {
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, state: ElementState) -> GameResult {
+        canvas.draw(
+            &Quad,
+            DrawParam::new()
+                .dest_rect(self.bounds)
+                .color(theme::current().background),
+        );
+        self.clip(ctx, canvas)?;
+
+        let first = (self.scroll / self.item_height) as usize;
+        let visible = (self.bounds.h / self.item_height).ceil() as usize + 1;
+        for index in first..(first + visible).min(self.items.len()) {
+            self.draw_item(canvas, index, state.focused);
+        }
+        self.draw_scrollbar(canvas);
+
+        canvas.set_default_scissor_rect();
+
+        let theme = theme::current();
+        let color = if state.focused {
+            theme.focused
+        } else {
+            theme.border
+        };
+        canvas.draw(&self.mesh, DrawParam::new().color(color));
+
+        Ok(())
+    }
+
+    fn contains_point(&self, _ctx: &mut Context, point: &Point2<f32>) -> bool {
+        self.bounds.contains(*point)
+    }
+
+    fn on_click(&mut self, ctx: &mut Context, _button: MouseButton) -> Option<T> {
+        // Mouse clicks pick an item when the button is released
+        if std::mem::take(&mut self.mouse_handled) {
+            return None;
+        }
+
+        // Activated with the keyboard or a gamepad
+        self.select(ctx, self.highlighted)
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn on_press(&mut self, _ctx: &mut Context, point: &Point2<f32>) {
+        self.mouse_handled = true;
+        self.drag = Some(Drag {
+            start_y: point.y,
+            start_scroll: self.scroll,
+            scrolling: false,
+        });
+        if let Some(index) = self.item_at(point) {
+            self.highlighted = index;
+        }
+    }
+
+    fn on_drag(&mut self, _ctx: &mut Context, point: &Point2<f32>) -> Option<T> {
+        if let Some(drag) = &mut self.drag {
+            let distance = point.y - drag.start_y;
+            if distance.abs() > DRAG_THRESHOLD {
+                drag.scrolling = true;
+            }
+            if drag.scrolling {
+                let scroll = drag.start_scroll - distance;
+                self.set_scroll(scroll);
+            }
+        }
+        None
+    }
+
+    fn on_release(&mut self, ctx: &mut Context, point: &Point2<f32>) -> Option<T> {
+        match self.drag.take() {
+            Some(drag) if !drag.scrolling => match self.item_at(point) {
+                Some(index) if index == self.highlighted => self.select(ctx, index),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn on_key(&mut self, _ctx: &mut Context, key_code: KeyCode) -> KeyResult<T> {
+        let page = ((self.bounds.h / self.item_height) as usize).max(1);
+        let last = self.items.len().saturating_sub(1);
+
+        // Up and down move the focus out of the list at its ends
+        let index = match key_code {
+            KeyCode::Up if self.highlighted > 0 => self.highlighted - 1,
+            KeyCode::Down if self.highlighted < last => self.highlighted + 1,
+            KeyCode::PageUp => self.highlighted.saturating_sub(page),
+            KeyCode::PageDown => (self.highlighted + page).min(last),
+            KeyCode::Home => 0,
+            KeyCode::End => last,
+            _ => return KeyResult::Ignored,
+        };
+        self.highlight_item(index);
+        KeyResult::Handled(None)
+    }
+
+    fn on_scroll(&mut self, delta: f32) {
+        self.set_scroll(self.scroll - delta * WHEEL_STEP);
+    }
+}
+
+fn max_scroll(content_height: f32, view_height: f32) -> f32 {
+    (content_height - view_height).max(0.0)
+}
+
+/// The smallest change to `scroll` that shows the whole item starting at `top`
+fn scroll_to_show(scroll: f32, top: f32, item_height: f32, view_height: f32) -> f32 {
+    if top < scroll {
+        top
+    } else if top + item_height > scroll + view_height {
+        top + item_height - view_height
+    } else {
+        scroll
+    }
+}
+
+#[cfg(test)]
+mod scroll_list_tests {
+    use super::{max_scroll, scroll_to_show};
+
+    #[test]
+    fn scroll_stops_at_the_end() {
+        assert_eq!(max_scroll(1000.0, 400.0), 600.0);
+        assert_eq!(max_scroll(300.0, 400.0), 0.0);
+    }
+
+    #[test]
+    fn scrolls_just_enough_to_show_item() {
+        // Below the view
+        assert_eq!(scroll_to_show(0.0, 400.0, 100.0, 300.0), 200.0);
+        // Above the view
+        assert_eq!(scroll_to_show(250.0, 100.0, 100.0, 300.0), 100.0);
+        // Already visible
+        assert_eq!(scroll_to_show(50.0, 100.0, 100.0, 300.0), 50.0);
+    }
+}
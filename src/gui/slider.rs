@@ -0,0 +1,191 @@
+use super::{
+    theme,
+    ui_layer::{ElementState, UiElement},
+};
+use ggez::{
+    event::MouseButton,
+    graphics::{Canvas, DrawParam, Quad, Rect},
+    Context, GameResult,
+};
+use nalgebra::Point2;
+
+pub type ValueChangeHandler<T> = dyn Fn(f32) -> Option<T>;
+
+/// Width of the value shown to the right of the track
+const VALUE_WIDTH: f32 = 50.0;
+const KNOB_WIDTH: f32 = 10.0;
+
+/// Round the value to a whole number of steps from the minimum, inside the range
+fn snap_value(value: f32, min: f32, max: f32, step: f32) -> f32 {
+    let steps = ((value - min) / step).round();
+    (min + steps * step).clamp(min, max)
+}
+
+/// Show as many decimals as the step needs
+pub(super) fn format_value(value: f32, step: f32) -> String {
+    let decimals = if step >= 1.0 {
+        0
+    } else if step >= 0.1 {
+        1
+    } else {
+        2
+    };
+    format!("{:.*}", decimals, value)
+}
+
+/// A horizontal slider for picking a number in a range, by dragging or with left and right
+pub struct Slider<T> {
+    bounds: Rect,
+    min: f32,
+    max: f32,
+    step: f32,
+    value: f32,
+    mouse_handled: bool,
+    handle_change: Box<ValueChangeHandler<T>>,
+}
+
+impl<T> Slider<T> {
+    pub fn new(
+        bounds: Rect,
+        min: f32,
+        max: f32,
+        value: f32,
+        on_change: Box<ValueChangeHandler<T>>,
+    ) -> Self {
+        Slider {
+            bounds,
+            min,
+            max,
+            step: (max - min) / 20.0,
+            value: value.clamp(min, max),
+            mouse_handled: false,
+            handle_change: on_change,
+        }
+    }
+
+    /// The value changes by whole steps, 1/20 of the range by default
+    pub fn step(mut self, step: f32) -> Self {
+        self.step = step;
+        self.value = snap_value(self.value, self.min, self.max, step);
+        self
+    }
+
+    fn track(&self) -> Rect {
+        Rect::new(
+            self.bounds.x + KNOB_WIDTH / 2.0,
+            self.bounds.y,
+            self.bounds.w - VALUE_WIDTH - KNOB_WIDTH,
+            self.bounds.h,
+        )
+    }
+
+    /// Move the value to the mouse position, returning whether it changed
+    fn set_from_x(&mut self, x: f32) -> bool {
+        let track = self.track();
+        let ratio = ((x - track.x) / track.w).clamp(0.0, 1.0);
+        let value = snap_value(
+            self.min + ratio * (self.max - self.min),
+            self.min,
+            self.max,
+            self.step,
+        );
+        let changed = value != self.value;
+        self.value = value;
+        changed
+    }
+}
+
+impl<T> UiElement<T> for Slider<T> {
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, state: ElementState) -> GameResult {
+        let theme = theme::current();
+        let track = self.track();
+        let line = Rect::new(track.x, track.center().y - 2.0, track.w, 4.0);
+        canvas.draw(
+            &Quad,
+            DrawParam::new().dest_rect(line).color(theme.secondary_text),
+        );
+
+        let ratio = (self.value - self.min) / (self.max - self.min);
+        let knob = Rect::new(
+            track.x + ratio * track.w - KNOB_WIDTH / 2.0,
+            self.bounds.y,
+            KNOB_WIDTH,
+            self.bounds.h,
+        );
+        let color = if state.focused || state.pressed {
+            theme.focused
+        } else {
+            theme.text
+        };
+        canvas.draw(&Quad, DrawParam::new().dest_rect(knob).color(color));
+
+        let text = theme.text(&format_value(self.value, self.step), self.bounds.h * 0.8);
+        let text_height = text.measure(ctx)?.y;
+        let dest = Point2::new(
+            self.bounds.x + self.bounds.w - VALUE_WIDTH + 5.0,
+            self.bounds.center().y - text_height / 2.0,
+        );
+        canvas.draw(&text, DrawParam::new().dest(dest).color(color));
+
+        Ok(())
+    }
+
+    fn contains_point(&self, _ctx: &mut Context, point: &Point2<f32>) -> bool {
+        self.bounds.contains(*point)
+    }
+
+    fn on_click(&mut self, _ctx: &mut Context, _button: MouseButton) -> Option<T> {
+        // Activating the slider with the keyboard does nothing, it is changed with left and right
+        if std::mem::take(&mut self.mouse_handled) {
+            (self.handle_change)(self.value)
+        } else {
+            None
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn is_adjustable(&self) -> bool {
+        true
+    }
+
+    fn on_press(&mut self, _ctx: &mut Context, point: &Point2<f32>) {
+        self.set_from_x(point.x);
+        self.mouse_handled = true;
+    }
+
+    fn on_drag(&mut self, _ctx: &mut Context, point: &Point2<f32>) -> Option<T> {
+        if self.set_from_x(point.x) {
+            (self.handle_change)(self.value)
+        } else {
+            None
+        }
+    }
+
+    fn on_adjust(&mut self, _ctx: &mut Context, increase: bool) -> Option<T> {
+        let delta = if increase { self.step } else { -self.step };
+        self.value = snap_value(self.value + delta, self.min, self.max, self.step);
+        (self.handle_change)(self.value)
+    }
+}
+
+#[cfg(test)]
+mod slider_tests {
+    use super::{format_value, snap_value};
+
+    #[test]
+    fn value_snaps_to_steps() {
+        assert_eq!(snap_value(0.37, 0.0, 1.0, 0.25), 0.25);
+        assert_eq!(snap_value(1.4, 0.0, 1.0, 0.25), 1.0);
+        assert_eq!(snap_value(47.0, 30.0, 180.0, 5.0), 45.0);
+    }
+
+    #[test]
+    fn decimals_follow_step() {
+        assert_eq!(format_value(90.0, 5.0), "90");
+        assert_eq!(format_value(1.26, 0.1), "1.3");
+        assert_eq!(format_value(0.25, 0.05), "0.25");
+    }
+}
@@ -0,0 +1,150 @@
+use super::{
+    slider::{format_value, ValueChangeHandler},
+    theme,
+    ui_layer::{ElementState, UiElement},
+};
+use ggez::{
+    event::MouseButton,
+    graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Quad, Rect},
+    Context, GameResult,
+};
+use nalgebra::Point2;
+
+/// A number with buttons to step it down and up, which can also be changed with left and right
+pub struct NumericStepper<T> {
+    bounds: Rect,
+    mesh: Mesh,
+    min: f32,
+    max: f32,
+    step: f32,
+    value: f32,
+    /// The change made by the last mouse press, reported by `on_click`
+    pressed_change: Option<bool>,
+    handle_change: Box<ValueChangeHandler<T>>,
+}
+
+impl<T> NumericStepper<T> {
+    pub fn new(
+        ctx: &mut Context,
+        bounds: Rect,
+        (min, max, step): (f32, f32, f32),
+        value: f32,
+        on_change: Box<ValueChangeHandler<T>>,
+    ) -> GameResult<Self> {
+        let mesh = Mesh::new_rectangle(
+            ctx,
+            DrawMode::stroke(theme::current().border_width),
+            bounds,
+            Color::WHITE,
+        )?;
+
+        Ok(NumericStepper {
+            bounds,
+            mesh,
+            min,
+            max,
+            step,
+            value: value.clamp(min, max),
+            pressed_change: None,
+            handle_change: on_change,
+        })
+    }
+
+    /// The buttons are squares at either end of the bounds
+    fn button_bounds(&self, increase: bool) -> Rect {
+        let x = if increase {
+            self.bounds.x + self.bounds.w - self.bounds.h
+        } else {
+            self.bounds.x
+        };
+        Rect::new(x, self.bounds.y, self.bounds.h, self.bounds.h)
+    }
+
+    fn change(&mut self, increase: bool) -> Option<T> {
+        let delta = if increase { self.step } else { -self.step };
+        let value = (self.value + delta).clamp(self.min, self.max);
+        if value == self.value {
+            return None;
+        }
+        self.value = value;
+        (self.handle_change)(value)
+    }
+
+    fn draw_centered(&self, ctx: &Context, canvas: &mut Canvas, text: &str, bounds: Rect) {
+        let theme = theme::current();
+        let text = theme.text(text, self.bounds.h * 0.8);
+        if let Ok(size) = text.measure(ctx) {
+            let dest = Point2::new(
+                bounds.center().x - size.x / 2.0,
+                bounds.center().y - size.y / 2.0,
+            );
+            canvas.draw(&text, DrawParam::new().dest(dest).color(theme.text));
+        }
+    }
+}
+
+impl<T> UiElement<T> for NumericStepper<T> {
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, state: ElementState) -> GameResult {
+        let theme = theme::current();
+        if state.pressed {
+            if let Some(increase) = self.pressed_change {
+                let bounds = self.button_bounds(increase);
+                canvas.draw(
+                    &Quad,
+                    DrawParam::new().dest_rect(bounds).color(theme.hover_fill),
+                );
+            }
+        }
+
+        let color = if state.focused {
+            theme.focused
+        } else {
+            theme.border
+        };
+        canvas.draw(&self.mesh, DrawParam::new().color(color));
+
+        self.draw_centered(ctx, canvas, "-", self.button_bounds(false));
+        self.draw_centered(ctx, canvas, "+", self.button_bounds(true));
+        self.draw_centered(
+            ctx,
+            canvas,
+            &format_value(self.value, self.step),
+            self.bounds,
+        );
+
+        Ok(())
+    }
+
+    fn contains_point(&self, _ctx: &mut Context, point: &Point2<f32>) -> bool {
+        self.bounds.contains(*point)
+    }
+
+    fn on_click(&mut self, _ctx: &mut Context, _button: MouseButton) -> Option<T> {
+        // Only the buttons react to the mouse, and the keyboard uses left and right instead
+        self.pressed_change
+            .and_then(|increase| self.change(increase))
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn is_adjustable(&self) -> bool {
+        true
+    }
+
+    fn on_press(&mut self, _ctx: &mut Context, point: &Point2<f32>) {
+        self.pressed_change = [false, true]
+            .into_iter()
+            .find(|&increase| self.button_bounds(increase).contains(*point));
+    }
+
+    fn on_release(&mut self, _ctx: &mut Context, _point: &Point2<f32>) -> Option<T> {
+        self.pressed_change = None;
+        None
+    }
+
+    fn on_adjust(&mut self, _ctx: &mut Context, increase: bool) -> Option<T> {
+        self.change(increase)
+    }
+}
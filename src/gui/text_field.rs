@@ -0,0 +1,298 @@
+use super::{
+    clipboard, theme,
+    ui_layer::{ElementState, KeyResult, UiElement},
+};
+use ggez::{
+    event::MouseButton,
+    graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Quad, Rect, Text},
+    input::keyboard::{KeyCode, KeyMods},
+    Context, GameResult,
+};
+use nalgebra::Point2;
+
+pub type TextChangeHandler<T> = dyn Fn(&str) -> Option<T>;
+
+/// A single line of editable text, which reports every change to its handler
+pub struct TextField<T> {
+    bounds: Rect,
+    mesh: Mesh,
+    fill_mesh: Mesh,
+    text: String,
+    placeholder: String,
+    /// Position of the caret, counted in characters
+    caret: usize,
+    /// The other end of the selection, if any text is selected
+    anchor: Option<usize>,
+    max_length: usize,
+    handle_change: Box<TextChangeHandler<T>>,
+}
+
+impl<T> TextField<T> {
+    pub fn new(
+        ctx: &mut Context,
+        bounds: Rect,
+        text: &str,
+        on_change: Box<TextChangeHandler<T>>,
+    ) -> GameResult<Self> {
+        let mesh = Mesh::new_rectangle(
+            ctx,
+            DrawMode::stroke(theme::current().border_width),
+            bounds,
+            Color::WHITE,
+        )?;
+        let fill_mesh = Mesh::new_rectangle(ctx, DrawMode::fill(), bounds, Color::WHITE)?;
+
+        Ok(TextField {
+            bounds,
+            mesh,
+            fill_mesh,
+            text: text.to_string(),
+            placeholder: String::new(),
+            caret: text.chars().count(),
+            anchor: None,
+            max_length: 32,
+            handle_change: on_change,
+        })
+    }
+
+    /// Text shown in grey while the field is empty
+    pub fn placeholder(mut self, placeholder: &str) -> Self {
+        self.placeholder = placeholder.to_string();
+        self
+    }
+
+    /// The maximal number of characters, which keeps the text inside the field
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    fn make_text(&self, content: &str) -> Text {
+        let theme = theme::current();
+        theme.text(content, self.bounds.h - 2.0 * theme.padding)
+    }
+
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(char_index)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+
+    /// Width of the first `chars` characters when drawn
+    fn text_width(&self, ctx: &Context, chars: usize) -> f32 {
+        if chars == 0 {
+            return 0.0;
+        }
+
+        let prefix = &self.text[..self.byte_index(chars)];
+        self.make_text(prefix)
+            .measure(ctx)
+            .map_or(0.0, |size| size.x)
+    }
+
+    /// The selected range of characters, ordered from start to end
+    fn selection(&self) -> Option<(usize, usize)> {
+        self.anchor
+            .filter(|&anchor| anchor != self.caret)
+            .map(|anchor| (anchor.min(self.caret), anchor.max(self.caret)))
+    }
+
+    fn selected_text(&self) -> Option<&str> {
+        self.selection()
+            .map(|(start, end)| &self.text[self.byte_index(start)..self.byte_index(end)])
+    }
+
+    /// Remove the selected text, returning whether anything was selected
+    fn delete_selection(&mut self) -> bool {
+        match self.selection() {
+            Some((start, end)) => {
+                let range = self.byte_index(start)..self.byte_index(end);
+                self.text.replace_range(range, "");
+                self.caret = start;
+                self.anchor = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replace the selection with the inserted characters, up to the maximal length
+    fn insert(&mut self, inserted: &str) {
+        self.delete_selection();
+
+        let room = self.max_length.saturating_sub(self.text.chars().count());
+        let accepted: String = inserted
+            .chars()
+            .filter(|&c| !c.is_control())
+            .take(room)
+            .collect();
+
+        let index = self.byte_index(self.caret);
+        self.text.insert_str(index, &accepted);
+        self.caret += accepted.chars().count();
+    }
+
+    /// Move the caret, extending the selection if shift is held
+    fn move_caret(&mut self, position: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.caret);
+        } else {
+            self.anchor = None;
+        }
+        self.caret = position;
+    }
+
+    fn changed(&self) -> KeyResult<T> {
+        KeyResult::Handled((self.handle_change)(&self.text))
+    }
+}
+
+impl<T> UiElement<T> for TextField<T> {
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, state: ElementState) -> GameResult {
+        let theme = theme::current();
+        if state.hovered && !state.focused {
+            canvas.draw(&self.fill_mesh, DrawParam::new().color(theme.hover_fill));
+        }
+
+        let color = if state.focused {
+            theme.focused
+        } else {
+            theme.border
+        };
+        canvas.draw(&self.mesh, DrawParam::new().color(color));
+
+        let origin = Point2::new(self.bounds.x + theme.padding, self.bounds.y + theme.padding);
+        let height = self.bounds.h - 2.0 * theme.padding;
+
+        if state.focused {
+            if let Some((start, end)) = self.selection() {
+                let x = self.text_width(ctx, start);
+                let w = self.text_width(ctx, end) - x;
+                let rect = Rect::new(origin.x + x, origin.y, w, height);
+                canvas.draw(
+                    &Quad,
+                    DrawParam::new().dest_rect(rect).color(theme.selection),
+                );
+            }
+        }
+
+        let (content, text_color) = if self.text.is_empty() {
+            (self.placeholder.as_str(), theme.secondary_text)
+        } else {
+            (self.text.as_str(), theme.text)
+        };
+        let text = self.make_text(content);
+        canvas.draw(&text, DrawParam::new().dest(origin).color(text_color));
+
+        // Blink the caret twice per second
+        let blink_on = ctx.time.time_since_start().as_millis() % 1000 < 500;
+        if state.focused && blink_on {
+            let x = self.text_width(ctx, self.caret);
+            let rect = Rect::new(origin.x + x, origin.y, 2.0, height);
+            canvas.draw(&Quad, DrawParam::new().dest_rect(rect).color(theme.text));
+        }
+
+        Ok(())
+    }
+
+    fn contains_point(&self, _ctx: &mut Context, point: &Point2<f32>) -> bool {
+        self.bounds.contains(*point)
+    }
+
+    fn on_click(&mut self, _ctx: &mut Context, _button: MouseButton) -> Option<T> {
+        None
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn accepts_text(&self) -> bool {
+        true
+    }
+
+    /// Place the caret at the character boundary closest to the mouse
+    fn on_press(&mut self, ctx: &mut Context, point: &Point2<f32>) {
+        let x = point.x - self.bounds.x - theme::current().padding;
+        let length = self.text.chars().count();
+        self.caret = (0..=length)
+            .min_by(|&a, &b| {
+                let distance_a = (self.text_width(ctx, a) - x).abs();
+                let distance_b = (self.text_width(ctx, b) - x).abs();
+                distance_a.total_cmp(&distance_b)
+            })
+            .unwrap_or(length);
+        self.anchor = None;
+    }
+
+    fn on_key(&mut self, ctx: &mut Context, key_code: KeyCode) -> KeyResult<T> {
+        let mods = ctx.keyboard.active_mods();
+        let select = mods.contains(KeyMods::SHIFT);
+        // Command is used for shortcuts on macOS
+        let shortcut = mods.intersects(KeyMods::CTRL | KeyMods::LOGO);
+        let length = self.text.chars().count();
+
+        match key_code {
+            KeyCode::Left => match self.selection() {
+                Some((start, _)) if !select => self.move_caret(start, false),
+                _ => self.move_caret(self.caret.saturating_sub(1), select),
+            },
+            KeyCode::Right => match self.selection() {
+                Some((_, end)) if !select => self.move_caret(end, false),
+                _ => self.move_caret((self.caret + 1).min(length), select),
+            },
+            KeyCode::Home => self.move_caret(0, select),
+            KeyCode::End => self.move_caret(length, select),
+            KeyCode::Back => {
+                if !self.delete_selection() && self.caret > 0 {
+                    self.caret -= 1;
+                    self.text.remove(self.byte_index(self.caret));
+                }
+                return self.changed();
+            }
+            KeyCode::Delete => {
+                if !self.delete_selection() && self.caret < length {
+                    self.text.remove(self.byte_index(self.caret));
+                }
+                return self.changed();
+            }
+            KeyCode::A if shortcut => {
+                self.anchor = Some(0);
+                self.caret = length;
+            }
+            KeyCode::C if shortcut => {
+                if let Some(selected) = self.selected_text() {
+                    clipboard::set_text(selected);
+                }
+            }
+            KeyCode::X if shortcut => {
+                if let Some(selected) = self.selected_text() {
+                    clipboard::set_text(selected);
+                    self.delete_selection();
+                    return self.changed();
+                }
+            }
+            KeyCode::V if shortcut => {
+                self.insert(&clipboard::get_text());
+                return self.changed();
+            }
+            // Space is typed as a character, so it should not activate the field
+            KeyCode::Space => {}
+            _ => return KeyResult::Ignored,
+        }
+
+        KeyResult::Handled(None)
+    }
+
+    fn on_text(&mut self, ctx: &mut Context, character: char) -> Option<T> {
+        // Shortcuts are handled as key presses
+        let mods = ctx.keyboard.active_mods();
+        if character.is_control() || mods.intersects(KeyMods::CTRL | KeyMods::LOGO) {
+            return None;
+        }
+
+        self.insert(&character.to_string());
+        (self.handle_change)(&self.text)
+    }
+}
@@ -0,0 +1,137 @@
+use ggez::{
+    graphics::{Color, FontData, PxScale, Text, TextFragment},
+    Context,
+};
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+/// Where the theme is read from, inside the resources directory
+const THEME_PATH: &str = "/theme.json";
+/// The name the theme's font is registered under
+const FONT_NAME: &str = "Theme";
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// The theme loaded at startup, or the default theme if none was loaded
+pub fn current() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+/// The colors, font and spacing used by all UI elements, read from `resources/theme.json`
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// A TTF file in the resources directory, e.g. `/fonts/DejaVuSans.ttf`
+    pub font: Option<String>,
+    #[serde(with = "rgba")]
+    pub text: Color,
+    /// Less important text, e.g. placeholders and details in lists
+    #[serde(with = "rgba")]
+    pub secondary_text: Color,
+    #[serde(with = "rgba")]
+    pub border: Color,
+    /// Border and text color of the element that has the keyboard focus
+    #[serde(with = "rgba")]
+    pub focused: Color,
+    #[serde(with = "rgba")]
+    pub disabled: Color,
+    #[serde(with = "rgba")]
+    pub hover_fill: Color,
+    #[serde(with = "rgba")]
+    pub pressed_fill: Color,
+    /// Background of lists, dialogs and notifications
+    #[serde(with = "rgba")]
+    pub background: Color,
+    #[serde(with = "rgba")]
+    pub selection: Color,
+    pub border_width: f32,
+    /// Space between the border of an element and its content
+    pub padding: f32,
+    /// Whether the font was loaded and registered
+    #[serde(skip)]
+    font_loaded: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            font: None,
+            text: Color::WHITE,
+            secondary_text: Color::new(0.6, 0.6, 0.6, 1.0),
+            border: Color::WHITE,
+            focused: Color::new(1.0, 0.85, 0.0, 1.0),
+            disabled: Color::new(0.4, 0.4, 0.4, 1.0),
+            hover_fill: Color::new(1.0, 1.0, 1.0, 0.15),
+            pressed_fill: Color::new(1.0, 1.0, 1.0, 0.3),
+            background: Color::new(0.1, 0.1, 0.1, 1.0),
+            selection: Color::new(0.3, 0.5, 1.0, 0.5),
+            border_width: 3.0,
+            padding: 5.0,
+            font_loaded: false,
+        }
+    }
+}
+
+impl Theme {
+    /// Read the theme and register its font, which has to happen before any UI is made
+    pub fn load(ctx: &mut Context) {
+        let mut theme = match ctx.fs.open(THEME_PATH) {
+            Ok(file) => serde_json::from_reader(file).unwrap_or_else(|e| {
+                eprintln!("Could not read theme: {}", e);
+                Theme::default()
+            }),
+            Err(_) => Theme::default(),
+        };
+
+        if let Some(path) = &theme.font {
+            match FontData::from_path(ctx, path) {
+                Ok(font) => {
+                    ctx.gfx.add_font(FONT_NAME, font);
+                    theme.font_loaded = true;
+                }
+                Err(e) => eprintln!("Could not load font {}: {}", path, e),
+            }
+        }
+
+        if THEME.set(theme).is_err() {
+            eprintln!("The theme was already loaded");
+        }
+    }
+
+    /// Make text in the theme's font
+    pub fn text(&self, content: &str, scale: f32) -> Text {
+        let mut text = Text::new(TextFragment::new(content));
+        text.set_scale(PxScale::from(scale));
+        if self.font_loaded {
+            text.set_font(FONT_NAME);
+        }
+        text
+    }
+}
+
+/// Colors are written as `[r, g, b, a]` with values from 0 to 1
+mod rgba {
+    use ggez::graphics::Color;
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let [r, g, b, a] = <[f32; 4]>::deserialize(deserializer)?;
+        Ok(Color::new(r, g, b, a))
+    }
+}
+
+#[cfg(test)]
+mod theme_tests {
+    use super::Theme;
+    use ggez::graphics::Color;
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let theme: Theme =
+            serde_json::from_str(r#"{ "focused": [0.0, 1.0, 0.0, 1.0], "padding": 8.0 }"#).unwrap();
+        assert_eq!(theme.focused, Color::new(0.0, 1.0, 0.0, 1.0));
+        assert_eq!(theme.padding, 8.0);
+        assert_eq!(theme.border, Color::WHITE);
+        assert_eq!(theme.font, None);
+    }
+}
@@ -0,0 +1,106 @@
+use super::{
+    layout::{Anchor, VerticalStack},
+    theme,
+};
+use ggez::{
+    graphics::{Canvas, DrawParam, Quad, Rect},
+    Context,
+};
+
+/// How long a toast is shown, in seconds
+const LIFETIME: f32 = 3.0;
+/// How long a toast takes to fade out at the end of its lifetime, in seconds
+const FADE_TIME: f32 = 1.0;
+const TOAST_HEIGHT: f32 = 36.0;
+const TEXT_SCALE: f32 = 20.0;
+
+struct Toast {
+    text: String,
+    age: f32,
+}
+
+/// Short messages stacked in the bottom right corner of the screen, which fade out after a while
+pub struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn new() -> Self {
+        Toasts { toasts: Vec::new() }
+    }
+
+    pub fn push(&mut self, text: String) {
+        self.toasts.push(Toast { text, age: 0.0 });
+    }
+
+    /// Age the toasts by `seconds` and remove the ones that have faded out
+    pub fn tick(&mut self, seconds: f32) {
+        for toast in &mut self.toasts {
+            toast.age += seconds;
+        }
+        self.toasts.retain(|toast| toast.age < LIFETIME);
+    }
+
+    pub fn draw(&self, ctx: &Context, canvas: &mut Canvas) {
+        let screen_coords = canvas.screen_coordinates().unwrap();
+        let height = self.toasts.len() as f32 * (TOAST_HEIGHT + 10.0);
+        let column = Anchor::BottomRight.place(screen_coords, screen_coords.w / 2.0, height, 10.0);
+        let mut stack = VerticalStack::new(column, 10.0);
+        let theme = theme::current();
+
+        for toast in &self.toasts {
+            let alpha = toast_alpha(toast.age);
+            let text = theme.text(&toast.text, TEXT_SCALE);
+            let text_width = text.measure(ctx).map_or(column.w, |size| size.x);
+
+            // Right align the toasts, fitting the background to the text
+            let row = stack.next(TOAST_HEIGHT);
+            let width = (text_width + 20.0).min(row.w);
+            let bounds = Rect::new(row.x + row.w - width, row.y, width, row.h);
+
+            let mut background = theme.background;
+            background.a *= alpha;
+            let mut text_color = theme.text;
+            text_color.a *= alpha;
+            canvas.draw(&Quad, DrawParam::new().dest_rect(bounds).color(background));
+            canvas.draw(
+                &text,
+                DrawParam::new()
+                    .dest([
+                        bounds.x + 10.0,
+                        bounds.y + (TOAST_HEIGHT - TEXT_SCALE) / 2.0,
+                    ])
+                    .color(text_color),
+            );
+        }
+    }
+}
+
+/// Toasts are opaque until they start to fade out at the end of their lifetime
+fn toast_alpha(age: f32) -> f32 {
+    ((LIFETIME - age) / FADE_TIME).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod toast_tests {
+    use super::{toast_alpha, Toasts, FADE_TIME, LIFETIME};
+
+    #[test]
+    fn toast_fades_at_end_of_lifetime() {
+        assert_eq!(toast_alpha(0.0), 1.0);
+        assert_eq!(toast_alpha(LIFETIME - FADE_TIME), 1.0);
+        assert_eq!(toast_alpha(LIFETIME - FADE_TIME / 2.0), 0.5);
+        assert_eq!(toast_alpha(LIFETIME), 0.0);
+    }
+
+    #[test]
+    fn old_toasts_are_removed() {
+        let mut toasts = Toasts::new();
+        toasts.push(String::from("First"));
+        toasts.tick(LIFETIME - 0.5);
+        toasts.push(String::from("Second"));
+        toasts.tick(1.0);
+        assert_eq!(toasts.toasts.len(), 1);
+        assert_eq!(toasts.toasts[0].text, "Second");
+    }
+}
@@ -1,19 +1,103 @@
-use ggez::{event::MouseButton, graphics::Canvas, Context, GameResult};
+use ggez::{
+    event::MouseButton,
+    graphics::Canvas,
+    input::{
+        gamepad::gilrs::{Axis, Button as GamepadButton},
+        keyboard::KeyCode,
+    },
+    Context, GameResult,
+};
 use nalgebra::Point2;
 
+/// The interaction state of an element, used to highlight it when drawing
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ElementState {
+    pub hovered: bool,
+    pub focused: bool,
+    pub pressed: bool,
+}
+
 pub trait UiElement<T> {
-    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult;
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, state: ElementState) -> GameResult;
     fn contains_point(&self, ctx: &mut Context, point: &Point2<f32>) -> bool;
-    fn on_click(&self, ctx: &mut Context, button: MouseButton) -> Option<T>;
+    fn on_click(&mut self, ctx: &mut Context, button: MouseButton) -> Option<T>;
+
+    /// Whether the element can be selected with the keyboard or a gamepad
+    fn is_focusable(&self) -> bool {
+        false
+    }
+
+    /// Whether the element takes typed characters while it is focused
+    fn accepts_text(&self) -> bool {
+        false
+    }
+
+    /// Whether the element has a value that left and right change, instead of moving the focus
+    fn is_adjustable(&self) -> bool {
+        false
+    }
+
+    /// Called when the element is pressed with the mouse, before `on_click`
+    fn on_press(&mut self, _ctx: &mut Context, _point: &Point2<f32>) {}
+
+    /// Called when the mouse moves while the element is pressed
+    fn on_drag(&mut self, _ctx: &mut Context, _point: &Point2<f32>) -> Option<T> {
+        None
+    }
+
+    /// Called when the mouse is released after pressing the element
+    fn on_release(&mut self, ctx: &mut Context, point: &Point2<f32>) -> Option<T> {
+        self.on_drag(ctx, point)
+    }
+
+    /// Called when the element loses the focus
+    fn on_blur(&mut self) {}
+
+    /// Called with key presses while the element is focused, before they are used for navigation
+    fn on_key(&mut self, _ctx: &mut Context, _key_code: KeyCode) -> KeyResult<T> {
+        KeyResult::Ignored
+    }
+
+    /// Called with typed characters while the element is focused
+    fn on_text(&mut self, _ctx: &mut Context, _character: char) -> Option<T> {
+        None
+    }
+
+    /// Step the value of an adjustable element up or down
+    fn on_adjust(&mut self, _ctx: &mut Context, _increase: bool) -> Option<T> {
+        None
+    }
+
+    /// Called when the mouse wheel is turned over the element, `delta` is positive away from the user
+    fn on_scroll(&mut self, _delta: f32) {}
 }
 
+/// Whether a focused element used a key press, and the event it produced
+pub enum KeyResult<T> {
+    Ignored,
+    Handled(Option<T>),
+}
+
+/// How far a stick has to be pushed to move the focus
+const STICK_THRESHOLD: f32 = 0.5;
+
 pub struct UiLayer<T> {
     elements: Vec<Box<dyn UiElement<T>>>,
+    focused: Option<usize>,
+    hovered: Option<usize>,
+    pressed: Option<usize>,
+    stick_engaged: bool,
 }
 
 impl<T> UiLayer<T> {
     pub fn new() -> Self {
-        UiLayer { elements: vec![] }
+        UiLayer {
+            elements: vec![],
+            focused: None,
+            hovered: None,
+            pressed: None,
+            stick_engaged: false,
+        }
     }
 
     pub fn add<E: 'static + UiElement<T>>(&mut self, element: E) {
@@ -21,13 +105,26 @@ impl<T> UiLayer<T> {
     }
 
     pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
-        for element in &self.elements {
-            element.draw(ctx, canvas)?;
+        for (i, element) in self.elements.iter().enumerate() {
+            let state = ElementState {
+                hovered: self.hovered == Some(i),
+                focused: self.focused == Some(i),
+                pressed: self.pressed == Some(i),
+            };
+            element.draw(ctx, canvas, state)?;
         }
 
         Ok(())
     }
 
+    /// Whether the point is on any of the elements, so the click should not reach the view below
+    pub fn contains_point(&self, ctx: &mut Context, x: f32, y: f32) -> bool {
+        let point = Point2::new(x, y);
+        self.elements
+            .iter()
+            .any(|element| element.contains_point(ctx, &point))
+    }
+
     pub fn mouse_press(
         &mut self,
         ctx: &mut Context,
@@ -37,9 +134,23 @@ impl<T> UiLayer<T> {
     ) -> Vec<T> {
         let point = Point2::new(x, y);
 
+        // Clicking outside of the focusable elements clears the focus
+        let clicked = (0..self.elements.len()).rev().find(|&i| {
+            self.elements[i].is_focusable() && self.elements[i].contains_point(ctx, &point)
+        });
+        self.set_focus(clicked);
+
         let mut events = Vec::new();
-        for element in &self.elements {
+        for (i, element) in self.elements.iter_mut().enumerate() {
             if element.contains_point(ctx, &point) {
+                if element.is_focusable() {
+                    if clicked != Some(i) {
+                        // Only the topmost element takes the press, e.g. over an open dropdown
+                        continue;
+                    }
+                    self.pressed = Some(i);
+                    element.on_press(ctx, &point);
+                }
                 if let Some(e) = element.on_click(ctx, button) {
                     events.push(e)
                 }
@@ -47,4 +158,261 @@ impl<T> UiLayer<T> {
         }
         events
     }
+
+    /// End a drag, letting the pressed element react to the final mouse position
+    pub fn mouse_release(&mut self, ctx: &mut Context, x: f32, y: f32) -> Vec<T> {
+        let point = Point2::new(x, y);
+        self.pressed
+            .take()
+            .and_then(|i| self.elements.get_mut(i))
+            .and_then(|element| element.on_release(ctx, &point))
+            .into_iter()
+            .collect()
+    }
+
+    /// Update the hovered element and drag the pressed element
+    pub fn mouse_motion(&mut self, ctx: &mut Context, x: f32, y: f32) -> Vec<T> {
+        let point = Point2::new(x, y);
+        self.hovered = self
+            .elements
+            .iter()
+            .rposition(|element| element.is_focusable() && element.contains_point(ctx, &point));
+
+        self.pressed
+            .and_then(|i| self.elements.get_mut(i))
+            .and_then(|element| element.on_drag(ctx, &point))
+            .into_iter()
+            .collect()
+    }
+
+    /// Scroll the topmost element under the mouse
+    pub fn mouse_wheel(&mut self, ctx: &mut Context, x: f32, y: f32, delta: f32) {
+        let point = Point2::new(x, y);
+        if let Some(element) = self
+            .elements
+            .iter_mut()
+            .rev()
+            .find(|element| element.contains_point(ctx, &point))
+        {
+            element.on_scroll(delta);
+        }
+    }
+
+    /// Focus the first focusable element, e.g. so Enter picks the default button of a dialog
+    pub fn focus_first(&mut self) {
+        self.set_focus(None);
+        self.move_focus(true);
+    }
+
+    /// The position of the focused element among the focusable ones, which stays the same
+    /// when the layer is made again with the same elements
+    pub fn focus_position(&self) -> Option<usize> {
+        let focused = self.focused?;
+        Some(
+            (0..focused)
+                .filter(|&i| self.elements[i].is_focusable())
+                .count(),
+        )
+    }
+
+    /// Focus the focusable element at the position returned by `focus_position`
+    pub fn restore_focus(&mut self, position: Option<usize>) {
+        let focused = position.and_then(|position| {
+            (0..self.elements.len())
+                .filter(|&i| self.elements[i].is_focusable())
+                .nth(position)
+        });
+        self.set_focus(focused);
+    }
+
+    /// Whether a text field is focused, so key presses should go to it rather than to shortcuts
+    pub fn has_text_focus(&self) -> bool {
+        self.focused
+            .and_then(|i| self.elements.get(i))
+            .is_some_and(|element| element.accepts_text())
+    }
+
+    /// Move the focus with Tab or the arrow keys and activate the focused element with Enter.
+    /// The focused element gets the key first, and Escape leaves a focused text field.
+    pub fn key_press(&mut self, ctx: &mut Context, key_code: KeyCode) -> Vec<T> {
+        if let Some(element) = self.focused.and_then(|i| self.elements.get_mut(i)) {
+            if let KeyResult::Handled(event) = element.on_key(ctx, key_code) {
+                return event.into_iter().collect();
+            }
+        }
+
+        match key_code {
+            KeyCode::Escape if self.has_text_focus() => self.set_focus(None),
+            KeyCode::Tab => {
+                let backwards = ctx.keyboard.is_key_pressed(KeyCode::LShift)
+                    || ctx.keyboard.is_key_pressed(KeyCode::RShift);
+                self.move_focus(!backwards)
+            }
+            KeyCode::Left => return self.adjust_or_move_focus(ctx, false),
+            KeyCode::Right => return self.adjust_or_move_focus(ctx, true),
+            KeyCode::Up => self.move_focus(false),
+            KeyCode::Down => self.move_focus(true),
+            KeyCode::Return | KeyCode::NumpadEnter | KeyCode::Space => {
+                return self.activate_focused(ctx)
+            }
+            _ => {}
+        }
+        Vec::new()
+    }
+
+    /// Pass a typed character to the focused element
+    pub fn text_input(&mut self, ctx: &mut Context, character: char) -> Vec<T> {
+        self.focused
+            .and_then(|i| self.elements.get_mut(i))
+            .and_then(|element| element.on_text(ctx, character))
+            .into_iter()
+            .collect()
+    }
+
+    /// Move the focus with the D-pad
+    pub fn gamepad_press(&mut self, ctx: &mut Context, button: GamepadButton) -> Vec<T> {
+        match button {
+            GamepadButton::DPadLeft => self.adjust_or_move_focus(ctx, false),
+            GamepadButton::DPadRight => self.adjust_or_move_focus(ctx, true),
+            GamepadButton::DPadUp => self.navigate(ctx, false),
+            GamepadButton::DPadDown => self.navigate(ctx, true),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Move the focus with the left stick, once per push
+    pub fn gamepad_axis(&mut self, ctx: &mut Context, axis: Axis, value: f32) -> Vec<T> {
+        if axis != Axis::LeftStickY {
+            return Vec::new();
+        }
+
+        if value.abs() < STICK_THRESHOLD {
+            self.stick_engaged = false;
+        } else if !self.stick_engaged {
+            self.stick_engaged = true;
+            // The stick's y axis points up
+            return self.navigate(ctx, value < 0.0);
+        }
+        Vec::new()
+    }
+
+    fn set_focus(&mut self, focused: Option<usize>) {
+        if self.focused != focused {
+            if let Some(element) = self.focused.and_then(|i| self.elements.get_mut(i)) {
+                element.on_blur();
+            }
+        }
+        self.focused = focused;
+    }
+
+    fn move_focus(&mut self, forward: bool) {
+        let focusable: Vec<usize> = (0..self.elements.len())
+            .filter(|&i| self.elements[i].is_focusable())
+            .collect();
+        if focusable.is_empty() {
+            return;
+        }
+
+        let position = self
+            .focused
+            .and_then(|focused| focusable.iter().position(|&i| i == focused));
+        let next = match (position, forward) {
+            (None, true) => 0,
+            (None, false) => focusable.len() - 1,
+            (Some(p), true) => (p + 1) % focusable.len(),
+            (Some(p), false) => (p + focusable.len() - 1) % focusable.len(),
+        };
+        self.set_focus(Some(focusable[next]));
+    }
+
+    /// Gamepad up and down act like the arrow keys, so lists can use them before the focus moves
+    fn navigate(&mut self, ctx: &mut Context, forward: bool) -> Vec<T> {
+        let key_code = if forward { KeyCode::Down } else { KeyCode::Up };
+        if let Some(element) = self.focused.and_then(|i| self.elements.get_mut(i)) {
+            if let KeyResult::Handled(event) = element.on_key(ctx, key_code) {
+                return event.into_iter().collect();
+            }
+        }
+
+        self.move_focus(forward);
+        Vec::new()
+    }
+
+    /// Left and right change the value of sliders and similar elements, and move the focus otherwise
+    fn adjust_or_move_focus(&mut self, ctx: &mut Context, increase: bool) -> Vec<T> {
+        match self.focused.and_then(|i| self.elements.get_mut(i)) {
+            Some(element) if element.is_adjustable() => {
+                element.on_adjust(ctx, increase).into_iter().collect()
+            }
+            _ => {
+                self.move_focus(increase);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Activate the focused element like a click, e.g. with the gamepad button bound to confirm
+    pub fn activate_focused(&mut self, ctx: &mut Context) -> Vec<T> {
+        self.focused
+            .and_then(|i| self.elements.get_mut(i))
+            .and_then(|element| element.on_click(ctx, MouseButton::Left))
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod ui_layer_tests {
+    use super::{ElementState, UiElement, UiLayer};
+    use ggez::{event::MouseButton, graphics::Canvas, Context, GameResult};
+    use nalgebra::Point2;
+
+    struct TestElement {
+        focusable: bool,
+    }
+
+    impl UiElement<()> for TestElement {
+        fn draw(
+            &self,
+            _ctx: &mut Context,
+            _canvas: &mut Canvas,
+            _state: ElementState,
+        ) -> GameResult {
+            Ok(())
+        }
+
+        fn contains_point(&self, _ctx: &mut Context, _point: &Point2<f32>) -> bool {
+            false
+        }
+
+        fn on_click(&mut self, _ctx: &mut Context, _button: MouseButton) -> Option<()> {
+            None
+        }
+
+        fn is_focusable(&self) -> bool {
+            self.focusable
+        }
+    }
+
+    fn layer(focusable: &[bool]) -> UiLayer<()> {
+        let mut layer = UiLayer::new();
+        for &focusable in focusable {
+            layer.add(TestElement { focusable });
+        }
+        layer
+    }
+
+    #[test]
+    fn focus_is_restored_past_new_labels() {
+        let mut old = layer(&[true, false, true, true]);
+        old.focus_first();
+        old.move_focus(true);
+        let position = old.focus_position();
+        assert_eq!(position, Some(1));
+
+        // A label was added in front of the focused element
+        let mut new = layer(&[true, false, false, true, true]);
+        new.restore_focus(position);
+        assert_eq!(new.focused, Some(3));
+    }
 }
@@ -1,18 +1,36 @@
 #![windows_subsystem = "windows"]
 
+mod campaign;
+mod config;
+mod controls;
 mod editor;
 mod game;
 mod gui;
+mod preferences;
 mod state;
 mod view;
 
 use ggez::GameResult;
 use ggez::{conf, event, ContextBuilder};
 use state::State;
+use std::{env, path::PathBuf};
 
 fn main() -> GameResult {
-    let (mut ctx, event_loop) = ContextBuilder::new("stealth_game", "Gabaa")
+    let mut context_builder = ContextBuilder::new("stealth_game", "Gabaa");
+
+    // Use the resources in the project directory when started with cargo
+    if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+        context_builder =
+            context_builder.add_resource_path(PathBuf::from(manifest_dir).join("resources"));
+    }
+
+    let (mut ctx, event_loop) = context_builder
         .window_setup(conf::WindowSetup::default().title("Stealth Game!!!"))
+        .window_mode(
+            conf::WindowMode::default()
+                .resizable(true)
+                .min_dimensions(640.0, 480.0),
+        )
         .build()?;
 
     let state = State::new(&mut ctx)?;
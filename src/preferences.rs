@@ -0,0 +1,65 @@
+use crate::config;
+use ggez::Context;
+use serde::{Deserialize, Serialize};
+use std::{io, path::PathBuf};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// Scales how fast guards notice the player
+    pub fn detection_multiplier(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.6,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+}
+
+/// Settings other than the controls, stored as `preferences.json` in the user config directory
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// Master volume from 0.0 to 1.0
+    pub volume: f32,
+    pub difficulty: Difficulty,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            volume: 0.8,
+            difficulty: Difficulty::Normal,
+        }
+    }
+}
+
+impl Preferences {
+    fn path(ctx: &Context) -> PathBuf {
+        ctx.fs.user_config_dir().join("preferences.json")
+    }
+
+    pub fn load(ctx: &Context) -> Self {
+        config::load_json(&Self::path(ctx))
+    }
+
+    pub fn save(&self, ctx: &Context) -> io::Result<()> {
+        config::save_json(&Self::path(ctx), self)
+    }
+}
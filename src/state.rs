@@ -1,22 +1,58 @@
-use crate::view::{main_menu::MainMenuView, View, ViewEvent};
+use crate::{
+    gui::{layout::dpi_scale, theme::Theme, toast::Toasts},
+    view::{main_menu::MainMenuView, View, ViewEvent},
+};
 use ggez::{
     event,
-    graphics::{Canvas, Color},
+    graphics::{Canvas, Color, DrawParam, Quad, Rect},
     input::{
+        gamepad::{
+            gilrs::{Axis, Button as GamepadButton},
+            GamepadId,
+        },
         keyboard::{KeyCode, KeyInput},
         mouse::MouseButton,
     },
     Context, GameResult,
 };
 
+/// How long a fade between views takes in seconds, half of it fading out and half fading in
+const TRANSITION_TIME: f32 = 0.4;
+
+/// The screen in logical pixels, so views have the same size on every screen
+fn logical_screen(ctx: &Context) -> Rect {
+    let (width, height) = ctx.gfx.drawable_size();
+    let scale = dpi_scale(ctx);
+    Rect::new(0.0, 0.0, width / scale, height / scale)
+}
+
+/// A fade to black and back, changing the views while the screen is black
+struct Transition {
+    /// The events to apply halfway through, taken once they are applied
+    events: Option<Vec<ViewEvent>>,
+    elapsed: f32,
+}
+
+/// How dark the screen is during a transition, from 0 to 1 and back to 0
+fn fade_alpha(elapsed: f32) -> f32 {
+    let half = TRANSITION_TIME / 2.0;
+    (1.0 - (elapsed - half).abs() / half).clamp(0.0, 1.0)
+}
+
 pub struct State {
     view_stack: Vec<Box<dyn View>>,
+    toasts: Toasts,
+    transition: Option<Transition>,
 }
 
 impl State {
     pub fn new(ctx: &mut Context) -> GameResult<Self> {
+        Theme::load(ctx);
+
         Ok(State {
             view_stack: vec![Box::new(MainMenuView::new(ctx)?)],
+            toasts: Toasts::new(),
+            transition: None,
         })
     }
 
@@ -26,6 +62,11 @@ impl State {
     }
 
     fn receive_input(&mut self, ctx: &mut Context, input: Input) {
+        // The views are about to change, so they should not react to input
+        if self.transition.is_some() {
+            return;
+        }
+
         if let Some(view) = self.top_view() {
             let events = view.receive_input(ctx, input);
             self.handle_events(ctx, events)
@@ -37,8 +78,32 @@ impl State {
             match event {
                 ViewEvent::PopView => {
                     self.view_stack.pop();
+                    if let Some(view) = self.top_view() {
+                        view.resume(ctx);
+                    }
                 }
                 ViewEvent::PushView(view) => self.view_stack.push(view),
+                ViewEvent::ReplaceView(view) => {
+                    self.view_stack.pop();
+                    self.view_stack.push(view);
+                }
+                ViewEvent::PopToRoot => {
+                    self.view_stack.truncate(1);
+                    if let Some(view) = self.top_view() {
+                        view.resume(ctx);
+                    }
+                }
+                ViewEvent::Transition(events) => match self.transition {
+                    // Already fading, so there is no need to fade again
+                    Some(_) => self.handle_events(ctx, events),
+                    None => {
+                        self.transition = Some(Transition {
+                            events: Some(events),
+                            elapsed: 0.0,
+                        })
+                    }
+                },
+                ViewEvent::ShowToast(text) => self.toasts.push(text),
             }
 
             if self.view_stack.is_empty() {
@@ -46,15 +111,45 @@ impl State {
             }
         }
     }
+
+    /// Tick the top view, the views below it are paused
+    fn tick_views(&mut self, ctx: &mut Context) {
+        if let Some(view) = self.top_view() {
+            let events = view.tick(ctx);
+            self.handle_events(ctx, events);
+        }
+    }
+
+    fn tick_transition(&mut self, ctx: &mut Context, seconds: f32) {
+        let Some(transition) = &mut self.transition else {
+            return;
+        };
+
+        transition.elapsed += seconds;
+        let events = match transition.elapsed >= TRANSITION_TIME / 2.0 {
+            true => transition.events.take(),
+            false => None,
+        };
+        if transition.elapsed >= TRANSITION_TIME {
+            self.transition = None;
+        }
+
+        if let Some(events) = events {
+            self.handle_events(ctx, events);
+        }
+    }
 }
 
 impl event::EventHandler for State {
     fn update(&mut self, ctx: &mut Context) -> GameResult {
         while ctx.time.check_update_time(60) {
-            if let Some(view) = self.top_view() {
-                let events = view.tick(ctx);
-                self.handle_events(ctx, events);
+            // The views are paused while fading between them
+            if self.transition.is_some() {
+                self.tick_transition(ctx, 1.0 / 60.0);
+            } else {
+                self.tick_views(ctx);
             }
+            self.toasts.tick(1.0 / 60.0);
         }
 
         Ok(())
@@ -62,11 +157,26 @@ impl event::EventHandler for State {
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         let mut canvas = Canvas::from_frame(ctx, Color::BLACK);
+        canvas.set_screen_coordinates(logical_screen(ctx));
 
-        if let Some(view) = self.top_view() {
+        // Draw the views that show through transparent views on top of them, bottom first
+        let first_visible = self
+            .view_stack
+            .iter()
+            .rposition(|view| !view.is_transparent())
+            .unwrap_or(0);
+        for view in &mut self.view_stack[first_visible..] {
             view.draw(ctx, &mut canvas)?;
         }
 
+        if let Some(transition) = &self.transition {
+            let color = Color::new(0.0, 0.0, 0.0, fade_alpha(transition.elapsed));
+            let screen = logical_screen(ctx);
+            canvas.draw(&Quad, DrawParam::new().dest_rect(screen).color(color));
+        }
+
+        self.toasts.draw(ctx, &mut canvas);
+
         canvas.finish(ctx)
     }
 
@@ -84,6 +194,11 @@ impl event::EventHandler for State {
         Ok(())
     }
 
+    fn text_input_event(&mut self, ctx: &mut Context, character: char) -> GameResult {
+        self.receive_input(ctx, Input::CharTyped { character });
+        Ok(())
+    }
+
     fn mouse_button_down_event(
         &mut self,
         ctx: &mut Context,
@@ -91,6 +206,8 @@ impl event::EventHandler for State {
         x: f32,
         y: f32,
     ) -> GameResult {
+        let scale = dpi_scale(ctx);
+        let (x, y) = (x / scale, y / scale);
         self.receive_input(ctx, Input::MouseDown { button, x, y });
         Ok(())
     }
@@ -103,6 +220,8 @@ impl event::EventHandler for State {
         _dx: f32,
         _dy: f32,
     ) -> GameResult {
+        let scale = dpi_scale(ctx);
+        let (x, y) = (x / scale, y / scale);
         self.receive_input(ctx, Input::MouseMotion { x, y });
         Ok(())
     }
@@ -114,15 +233,99 @@ impl event::EventHandler for State {
         x: f32,
         y: f32,
     ) -> GameResult {
+        let scale = dpi_scale(ctx);
+        let (x, y) = (x / scale, y / scale);
         self.receive_input(ctx, Input::MouseUp { button, x, y });
         Ok(())
     }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, _x: f32, y: f32) -> GameResult {
+        // The event only has the scroll distance, so take the position from the mouse
+        let scale = dpi_scale(ctx);
+        let position = ctx.mouse.position();
+        let (x, y, delta) = (position.x / scale, position.y / scale, y);
+        self.receive_input(ctx, Input::MouseWheel { x, y, delta });
+        Ok(())
+    }
+
+    /// Lay out every view again, including the ones below the top view
+    fn resize_event(&mut self, ctx: &mut Context, _width: f32, _height: f32) -> GameResult {
+        for view in &mut self.view_stack {
+            view.resize(ctx);
+        }
+        Ok(())
+    }
+
+    fn gamepad_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        button: GamepadButton,
+        _id: GamepadId,
+    ) -> GameResult {
+        self.receive_input(ctx, Input::GamepadButtonDown { button });
+        Ok(())
+    }
+
+    fn gamepad_axis_event(
+        &mut self,
+        ctx: &mut Context,
+        axis: Axis,
+        value: f32,
+        _id: GamepadId,
+    ) -> GameResult {
+        self.receive_input(ctx, Input::GamepadAxis { axis, value });
+        Ok(())
+    }
 }
 
 pub enum Input {
-    MouseDown { button: MouseButton, x: f32, y: f32 },
-    MouseMotion { x: f32, y: f32 },
-    MouseUp { button: MouseButton, x: f32, y: f32 },
-    KeyDown { key_code: KeyCode },
-    KeyUp { key_code: KeyCode },
+    MouseDown {
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    },
+    MouseMotion {
+        x: f32,
+        y: f32,
+    },
+    MouseUp {
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    },
+    /// `delta` is positive when the wheel is turned away from the user
+    MouseWheel {
+        x: f32,
+        y: f32,
+        delta: f32,
+    },
+    KeyDown {
+        key_code: KeyCode,
+    },
+    KeyUp {
+        key_code: KeyCode,
+    },
+    CharTyped {
+        character: char,
+    },
+    GamepadButtonDown {
+        button: GamepadButton,
+    },
+    GamepadAxis {
+        axis: Axis,
+        value: f32,
+    },
+}
+
+#[cfg(test)]
+mod state_tests {
+    use super::{fade_alpha, TRANSITION_TIME};
+
+    #[test]
+    fn screen_is_black_halfway_through_transition() {
+        assert_eq!(fade_alpha(0.0), 0.0);
+        assert_eq!(fade_alpha(TRANSITION_TIME / 2.0), 1.0);
+        assert_eq!(fade_alpha(TRANSITION_TIME), 0.0);
+        assert!((fade_alpha(TRANSITION_TIME / 4.0) - 0.5).abs() < 1e-6);
+    }
 }
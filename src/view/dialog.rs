@@ -0,0 +1,196 @@
+use super::{View, ViewEvent};
+use crate::{
+    controls::{Action, Controls},
+    gui::{
+        button::Button,
+        label::Label,
+        layout::{Anchor, HorizontalStack, VerticalStack},
+        theme, UiLayer,
+    },
+    state::Input,
+};
+use ggez::{
+    graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Quad, Rect},
+    input::keyboard::KeyCode,
+    Context, GameResult,
+};
+
+/// Makes the events for confirming a dialog, after the dialog itself is popped
+pub type ConfirmHandler = dyn Fn(&mut Context) -> Vec<ViewEvent>;
+
+const DIM: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+const PANEL_WIDTH: f32 = 500.0;
+const PANEL_HEIGHT: f32 = 200.0;
+
+#[derive(Clone, Copy)]
+enum DialogEvent {
+    Confirm,
+    Cancel,
+}
+
+/// A message or question shown on top of the view below it, which gets no input until the
+/// dialog is closed
+pub struct DialogView {
+    title: String,
+    message: String,
+    /// The text of the confirm button, confirm dialogs also have a cancel button
+    confirm_text: String,
+    on_confirm: Option<Box<ConfirmHandler>>,
+    panel: Rect,
+    /// Loaded when the dialog is laid out, since dialogs are made without a context
+    controls: Controls,
+    ui_layer: Option<UiLayer<DialogEvent>>,
+}
+
+impl DialogView {
+    /// A dialog that only tells the user something
+    pub fn message(title: &str, message: &str) -> Self {
+        DialogView {
+            title: title.to_string(),
+            message: message.to_string(),
+            confirm_text: String::from("OK"),
+            on_confirm: None,
+            panel: Rect::default(),
+            controls: Controls::default(),
+            ui_layer: None,
+        }
+    }
+
+    /// A dialog asking the user to confirm an action, or to cancel it
+    pub fn confirm(
+        title: &str,
+        message: &str,
+        confirm_text: &str,
+        on_confirm: Box<ConfirmHandler>,
+    ) -> Self {
+        DialogView {
+            confirm_text: confirm_text.to_string(),
+            on_confirm: Some(on_confirm),
+            ..DialogView::message(title, message)
+        }
+    }
+
+    fn init_ui(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let mut ui_layer = UiLayer::new();
+        self.controls = Controls::load(ctx);
+
+        let screen_coords = canvas.screen_coordinates().unwrap();
+        self.panel = Anchor::Center.place(screen_coords, PANEL_WIDTH, PANEL_HEIGHT, 0.0);
+
+        let content =
+            Anchor::Center.place(self.panel, self.panel.w - 40.0, self.panel.h - 40.0, 0.0);
+        let mut stack = VerticalStack::new(content, 15.0);
+        ui_layer.add(Label::new(ctx, &self.title, stack.next(35.0)));
+        ui_layer.add(Label::new(ctx, &self.message, stack.next(25.0)));
+
+        let row = Anchor::Bottom.place(content, content.w, 40.0, 0.0);
+        let mut buttons = vec![(self.confirm_text.as_str(), DialogEvent::Confirm)];
+        if self.on_confirm.is_some() {
+            buttons.push(("Cancel", DialogEvent::Cancel));
+        }
+        let bounds = HorizontalStack::split(row, buttons.len(), 20.0);
+        for ((text, event), bounds) in buttons.into_iter().zip(bounds) {
+            ui_layer.add(Button::new(
+                ctx,
+                bounds,
+                Some(text),
+                Box::new(move |_| Some(event)),
+            )?);
+        }
+
+        ui_layer.focus_first();
+        self.ui_layer = Some(ui_layer);
+
+        Ok(())
+    }
+
+    /// The dialog closes on the first event, so it can only be confirmed once
+    fn handle_dialog_events(&self, ctx: &mut Context, events: Vec<DialogEvent>) -> Vec<ViewEvent> {
+        let mut view_events = Vec::new();
+
+        if let Some(event) = events.into_iter().next() {
+            view_events.push(ViewEvent::PopView);
+            if let (DialogEvent::Confirm, Some(on_confirm)) = (event, &self.on_confirm) {
+                view_events.extend(on_confirm(ctx));
+            }
+        }
+
+        view_events
+    }
+}
+
+impl View for DialogView {
+    fn tick(&mut self, _ctx: &mut Context) -> Vec<ViewEvent> {
+        Vec::new()
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        if self.ui_layer.is_none() {
+            self.init_ui(ctx, canvas)?;
+        }
+
+        let theme = theme::current();
+        let screen_coords = canvas.screen_coordinates().unwrap();
+        canvas.draw(&Quad, DrawParam::new().dest_rect(screen_coords).color(DIM));
+        canvas.draw(
+            &Quad,
+            DrawParam::new()
+                .dest_rect(self.panel)
+                .color(theme.background),
+        );
+        let border = Mesh::new_rectangle(
+            ctx,
+            DrawMode::stroke(theme.border_width),
+            self.panel,
+            theme.border,
+        )?;
+        canvas.draw(&border, DrawParam::new());
+
+        self.ui_layer.as_ref().unwrap().draw(ctx, canvas)
+    }
+
+    fn resize(&mut self, _ctx: &mut Context) {
+        self.ui_layer = None;
+    }
+
+    fn is_transparent(&self) -> bool {
+        true
+    }
+
+    fn receive_input(&mut self, ctx: &mut Context, input: Input) -> Vec<ViewEvent> {
+        let mut events = Vec::new();
+
+        if let Some(ui_layer) = &mut self.ui_layer {
+            match input {
+                Input::MouseDown { button, x, y } => {
+                    events.extend(ui_layer.mouse_press(ctx, button, x, y))
+                }
+                Input::KeyDown {
+                    key_code: KeyCode::Escape,
+                } => events.push(DialogEvent::Cancel),
+                Input::GamepadButtonDown { button }
+                    if self.controls.matches_button(Action::MenuBack, button) =>
+                {
+                    events.push(DialogEvent::Cancel)
+                }
+                Input::GamepadButtonDown { button }
+                    if self.controls.matches_button(Action::MenuConfirm, button) =>
+                {
+                    events.extend(ui_layer.activate_focused(ctx))
+                }
+                Input::GamepadButtonDown { button } => {
+                    events.extend(ui_layer.gamepad_press(ctx, button))
+                }
+                Input::GamepadAxis { axis, value } => {
+                    events.extend(ui_layer.gamepad_axis(ctx, axis, value))
+                }
+                Input::KeyDown { key_code } => events.extend(ui_layer.key_press(ctx, key_code)),
+                Input::MouseMotion { x, y } => events.extend(ui_layer.mouse_motion(ctx, x, y)),
+                Input::MouseUp { x, y, .. } => events.extend(ui_layer.mouse_release(ctx, x, y)),
+                _ => {}
+            };
+        }
+
+        self.handle_dialog_events(ctx, events)
+    }
+}
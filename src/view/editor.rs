@@ -1,56 +1,210 @@
-use super::{game::GameView, View, ViewEvent};
+use super::{
+    dialog::DialogView,
+    game::{load_level_info, GameView},
+    levels::{level_title, unreadable_level_item, LevelCache},
+    View, ViewEvent,
+};
 use crate::{
-    editor::{PolygonType, SelectionHandler, SelectionObject},
-    game::{actor::Actor, polygon::Polygon, rendering::Renderer, Game},
+    campaign::Campaign,
+    controls::{Action, Controls},
+    editor::{
+        copy_paste::CopiedObjects,
+        draw_tool::{DrawTarget, Shape, ShapeDrawing},
+        history::History,
+        snapping::{self, SnapGuide, SnapSource},
+        PolygonType, SelectionHandler, SelectionObject, Transform,
+    },
+    game::{
+        actor::Actor,
+        controller::Controller,
+        level_info::{GuardSettings, LevelInfo, LevelMetadata, PatrolMode, WaypointSettings},
+        rendering::Renderer,
+        Game,
+    },
     gui::{
         button::{Button, ButtonClickHandler},
+        checkbox::Checkbox,
+        clipboard,
+        dropdown::Dropdown,
+        label::Label,
+        layout::{Anchor, HorizontalStack, VerticalStack},
+        scroll_list::{ListItem, ScrollList},
+        slider::Slider,
+        stepper::NumericStepper,
+        text_field::TextField,
         UiLayer,
     },
     state::Input,
 };
 use ggez::{
+    event::MouseButton,
     graphics::{Canvas, Rect},
-    input::keyboard::KeyCode,
+    input::keyboard::{KeyCode, KeyMods},
     Context, GameResult,
 };
-use nalgebra::Point2;
-use std::{
-    fs::File,
-    path::{Path, PathBuf},
-};
+use nalgebra::{Point2, Vector2};
+use std::{fs::File, io, path::Path};
 
 pub const GRID_SIZE: f32 = 25.0;
+/// How many directions a guard can look in at one waypoint
+const MAX_LOOKS: usize = 4;
+/// Width of the labels in front of the widgets of the guard settings
+const LABEL_WIDTH: f32 = 90.0;
 
-fn snap_to_grid(point: Point2<f32>) -> Point2<f32> {
+pub fn snap_to_grid(point: Point2<f32>) -> Point2<f32> {
     let x = (point.x / GRID_SIZE).round() * GRID_SIZE;
     let y = (point.y / GRID_SIZE).round() * GRID_SIZE;
     Point2::new(x, y)
 }
 
+/// Turn a level name into a file name, keeping only lowercase letters, digits and underscores
+fn level_file_stem(level_name: &str) -> String {
+    level_name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            c if c.is_ascii_alphanumeric() => c.to_ascii_lowercase(),
+            _ => '_',
+        })
+        .collect()
+}
+
+/// A full screen panel shown instead of the editor UI
+#[derive(Clone, Copy)]
+enum Panel {
+    OpenLevel,
+    LevelInfo,
+}
+
+/// Makes the event for a change to one of the text fields of the level info
+type TextFieldEvent = fn(String) -> EditorEvent;
+
+/// Makes the event for a change to one of the coordinates in the inspector
+type CoordinateEvent = fn(f32) -> EditorEvent;
+
 enum EditorEvent {
     ViewEvent(ViewEvent),
-    CreateObstacle,
+    StartDrawing(DrawTarget),
+    /// Add the finished drawing to the level
+    AddShape(Vec<Point2<f32>>),
+    SetShape(Shape),
+    SetCircleSides(f32),
     CreateGuard,
     Preview,
+    ShowPanel(Panel),
+    ClosePanel,
+    OpenLevel(String),
+    SetLevelName(String),
+    SetTitle(String),
+    SetAuthor(String),
+    SetDescription(String),
+    SetTags(String),
+    SetDifficulty(f32),
+    SetParTime(f32),
+    SetAlwaysSnap(bool),
+    SetSnapToObjects(bool),
+    SetGuardSpeed(f32),
+    SetGuardViewAngle(f32),
+    SetGuardViewDistance(f32),
+    SetGuardFacing(f32),
+    SetPatrolMode(PatrolMode),
+    SetWaypointWait(f32),
+    SetGuardTurnSpeed(f32),
+    SetGuardStopsToTurn(bool),
+    /// Add a direction for the guard to look in while waiting at the selected waypoint
+    AddWaypointLook,
+    RemoveWaypointLook(usize),
+    SetWaypointLook(usize, f32),
+    SetLightLevel(f32),
+    SetPositionX(f32),
+    SetPositionY(f32),
+    /// Select a vertex of the inspected shape or a waypoint of the inspected guard
+    SelectPoint(usize),
+    TransformSelection(Transform),
+    DeleteSelection,
+    Copy,
+    Paste,
+    Duplicate,
+    Undo,
+    Redo,
     Save,
 }
 
+impl EditorEvent {
+    /// Whether the event changes a value shown in the inspector, so the inspector does not have
+    /// to be made again to show it
+    fn edits_properties(&self) -> bool {
+        matches!(
+            self,
+            EditorEvent::SetGuardSpeed(_)
+                | EditorEvent::SetGuardViewAngle(_)
+                | EditorEvent::SetGuardViewDistance(_)
+                | EditorEvent::SetGuardFacing(_)
+                | EditorEvent::SetPatrolMode(_)
+                | EditorEvent::SetWaypointWait(_)
+                | EditorEvent::SetGuardTurnSpeed(_)
+                | EditorEvent::SetGuardStopsToTurn(_)
+                | EditorEvent::SetWaypointLook(..)
+                | EditorEvent::SetLightLevel(_)
+                | EditorEvent::SetPositionX(_)
+                | EditorEvent::SetPositionY(_)
+        )
+    }
+}
+
 pub struct EditorView {
     game: Game,
     renderer: Renderer,
     ui: Option<UiLayer<EditorEvent>>,
     selection_handler: SelectionHandler,
     snap_to_grid: bool,
+    /// Snap even when the snap key is not held
+    always_snap: bool,
+    /// Snap dragged points to the vertices and edges of other objects
+    snap_to_objects: bool,
+    /// What the dragged point snapped to
+    snap_guide: Option<SnapGuide>,
+    /// Whether the mouse was pressed on the UI rather than on the level
+    pressing_ui: bool,
+    /// Where pasted objects are placed
+    mouse_pos: Point2<f32>,
+    /// The shape being drawn, which takes the mouse input until it is finished
+    drawing: Option<ShapeDrawing>,
+    /// The shape drawn by the create buttons
+    shape: Shape,
+    circle_sides: usize,
+    controls: Controls,
+    level_name: String,
+    /// The level loaded with the open dialog, which saving overwrites while the name is unchanged
+    opened_level: Option<String>,
+    panel: Option<Panel>,
+    levels: LevelCache,
+    history: History,
 }
 
 impl EditorView {
-    pub fn new(_ctx: &mut Context) -> GameResult<Self> {
+    pub fn new(ctx: &mut Context) -> GameResult<Self> {
+        let game = Game::new();
         Ok(EditorView {
-            game: Game::new(),
+            history: History::new(&game),
+            game,
             renderer: Renderer::new(),
             ui: None,
             selection_handler: SelectionHandler::new(),
             snap_to_grid: false,
+            always_snap: false,
+            snap_to_objects: false,
+            snap_guide: None,
+            pressing_ui: false,
+            mouse_pos: Point2::new(0.0, 0.0),
+            drawing: None,
+            shape: Shape::Polygon,
+            circle_sides: 8,
+            controls: Controls::load(ctx),
+            level_name: String::new(),
+            opened_level: None,
+            panel: None,
+            levels: LevelCache::new(),
         })
     }
 
@@ -58,80 +212,709 @@ impl EditorView {
         let mut ui = UiLayer::new();
 
         let screen_coords = canvas.screen_coordinates().unwrap();
-        ui.add(Self::init_obstacle_button(ctx, screen_coords)?);
-        ui.add(Self::init_guard_button(ctx, screen_coords)?);
-        ui.add(Self::init_preview_button(ctx, screen_coords)?);
-        ui.add(Self::init_save_button(ctx, screen_coords)?);
+
+        match self.panel {
+            Some(Panel::OpenLevel) => {
+                Self::init_open_dialog(ctx, &mut ui, &mut self.levels, screen_coords)?;
+                self.ui = Some(ui);
+                return Ok(());
+            }
+            Some(Panel::LevelInfo) => {
+                self.init_level_info_panel(ctx, &mut ui, screen_coords)?;
+                self.ui = Some(ui);
+                return Ok(());
+            }
+            None => {}
+        }
+
+        let top_column = Anchor::TopRight.place(screen_coords, 150.0, screen_coords.h, 10.0);
+        let mut stack = VerticalStack::new(top_column, 10.0);
+        let shape_bounds = stack.next(30.0);
+        if self.shape == Shape::Circle {
+            ui.add(Label::new(ctx, "Sides", stack.next(20.0)));
+            ui.add(self.init_circle_sides_stepper(ctx, stack.next(30.0))?);
+        }
+        stack.skip(10.0);
+        ui.add(Self::init_obstacle_button(ctx, stack.next(30.0))?);
+        ui.add(Self::init_guard_button(ctx, stack.next(30.0))?);
+        ui.add(Self::init_light_zone_button(ctx, stack.next(30.0))?);
+        ui.add(Self::init_end_area_button(ctx, stack.next(30.0))?);
+        // The shape list opens over the buttons below it, so it is added last
+        ui.add(self.init_shape_dropdown(ctx, shape_bounds)?);
+
+        let bottom_column = Anchor::BottomRight.place(screen_coords, 150.0, 300.0, 10.0);
+        let mut stack = VerticalStack::new(bottom_column, 10.0);
+        Self::init_history_buttons(ctx, &mut ui, stack.next(30.0))?;
+        ui.add(self.init_snap_checkbox(ctx, stack.next(25.0))?);
+        ui.add(self.init_snap_to_objects_checkbox(ctx, stack.next(25.0))?);
+        ui.add(Self::init_open_button(ctx, stack.next(30.0))?);
+        ui.add(self.init_level_name_field(ctx, stack.next(30.0))?);
+        ui.add(Self::init_level_info_button(ctx, stack.next(30.0))?);
+        ui.add(Self::init_save_button(ctx, stack.next(30.0))?);
+        ui.add(Self::init_preview_button(ctx, stack.next(30.0))?);
+
+        if !self.selection_handler.selection.is_empty() {
+            let column = Anchor::BottomLeft.place(screen_coords, 150.0, 150.0, 10.0);
+            let mut stack = VerticalStack::new(column, 10.0);
+            Self::init_selection_panel(ctx, &mut ui, &mut stack)?;
+        }
+
+        if let Some(object) = self.selection_handler.single() {
+            let column = Anchor::TopLeft.place(screen_coords, 200.0, screen_coords.h - 180.0, 10.0);
+            self.init_inspector(ctx, &mut ui, column, object)?;
+
+            // The guard settings do not fit below the inspector, so they get a column next to it
+            if let Some(index) = self.selected_guard() {
+                let column = Rect::new(
+                    column.x + column.w + 10.0,
+                    column.y,
+                    240.0,
+                    screen_coords.h - 20.0,
+                );
+                self.init_guard_panel(ctx, &mut ui, column, index, object)?;
+            }
+        }
 
         self.ui = Some(ui);
 
         Ok(())
     }
 
-    fn init_obstacle_button(
-        ctx: &mut Context,
-        screen_coords: Rect,
-    ) -> GameResult<Button<EditorEvent>> {
-        let bounds = Rect::new(
-            screen_coords.x + screen_coords.w - 160.0,
-            screen_coords.y + 10.0,
-            150.0,
-            30.0,
-        );
+    fn init_obstacle_button(ctx: &mut Context, bounds: Rect) -> GameResult<Button<EditorEvent>> {
         let on_click: Box<ButtonClickHandler<EditorEvent>> =
-            Box::new(|_| Some(EditorEvent::CreateObstacle));
+            Box::new(|_| Some(EditorEvent::StartDrawing(DrawTarget::Obstacle)));
         Button::new(ctx, bounds, Some("Create obstacle"), on_click)
     }
 
-    fn init_guard_button(
-        ctx: &mut Context,
-        screen_coords: Rect,
-    ) -> GameResult<Button<EditorEvent>> {
-        let bounds = Rect::new(
-            screen_coords.x + screen_coords.w - 160.0,
-            screen_coords.y + 50.0,
-            150.0,
-            30.0,
-        );
+    fn init_guard_button(ctx: &mut Context, bounds: Rect) -> GameResult<Button<EditorEvent>> {
         let on_click: Box<ButtonClickHandler<EditorEvent>> =
             Box::new(|_| Some(EditorEvent::CreateGuard));
         Button::new(ctx, bounds, Some("Create guard"), on_click)
     }
 
-    fn init_preview_button(
+    fn init_light_zone_button(ctx: &mut Context, bounds: Rect) -> GameResult<Button<EditorEvent>> {
+        let on_click: Box<ButtonClickHandler<EditorEvent>> =
+            Box::new(|_| Some(EditorEvent::StartDrawing(DrawTarget::LightZone)));
+        Button::new(ctx, bounds, Some("Create dark zone"), on_click)
+    }
+
+    fn init_end_area_button(ctx: &mut Context, bounds: Rect) -> GameResult<Button<EditorEvent>> {
+        let on_click: Box<ButtonClickHandler<EditorEvent>> =
+            Box::new(|_| Some(EditorEvent::StartDrawing(DrawTarget::EndArea)));
+        Button::new(ctx, bounds, Some("Create end area"), on_click)
+    }
+
+    fn init_shape_dropdown(
+        &self,
+        ctx: &mut Context,
+        bounds: Rect,
+    ) -> GameResult<Dropdown<EditorEvent>> {
+        Dropdown::new(
+            ctx,
+            bounds,
+            Shape::ALL.iter().map(|s| s.name().to_string()).collect(),
+            Shape::ALL
+                .iter()
+                .position(|&s| s == self.shape)
+                .unwrap_or_default(),
+            Box::new(|i| Some(EditorEvent::SetShape(Shape::ALL[i]))),
+        )
+    }
+
+    fn init_circle_sides_stepper(
+        &self,
+        ctx: &mut Context,
+        bounds: Rect,
+    ) -> GameResult<NumericStepper<EditorEvent>> {
+        NumericStepper::new(
+            ctx,
+            bounds,
+            (3.0, 32.0, 1.0),
+            self.circle_sides as f32,
+            Box::new(|sides| Some(EditorEvent::SetCircleSides(sides))),
+        )
+    }
+
+    fn init_preview_button(ctx: &mut Context, bounds: Rect) -> GameResult<Button<EditorEvent>> {
+        let on_click: Box<ButtonClickHandler<EditorEvent>> =
+            Box::new(|_| Some(EditorEvent::Preview));
+        Button::new(ctx, bounds, Some("Preview"), on_click)
+    }
+
+    fn init_open_button(ctx: &mut Context, bounds: Rect) -> GameResult<Button<EditorEvent>> {
+        let on_click: Box<ButtonClickHandler<EditorEvent>> =
+            Box::new(|_| Some(EditorEvent::ShowPanel(Panel::OpenLevel)));
+        Button::new(ctx, bounds, Some("Open level"), on_click)
+    }
+
+    fn init_level_info_button(ctx: &mut Context, bounds: Rect) -> GameResult<Button<EditorEvent>> {
+        let on_click: Box<ButtonClickHandler<EditorEvent>> =
+            Box::new(|_| Some(EditorEvent::ShowPanel(Panel::LevelInfo)));
+        Button::new(ctx, bounds, Some("Level info"), on_click)
+    }
+
+    /// Fields for the title, description and other information shown in the level selector
+    fn init_level_info_panel(
+        &self,
         ctx: &mut Context,
+        ui: &mut UiLayer<EditorEvent>,
         screen_coords: Rect,
-    ) -> GameResult<Button<EditorEvent>> {
-        let bounds = Rect::new(
-            screen_coords.x + screen_coords.w - 160.0,
-            screen_coords.y + screen_coords.h - 40.0,
-            150.0,
+    ) -> GameResult {
+        let column = Anchor::Top.place(
+            screen_coords,
+            screen_coords.w / 2.0,
+            screen_coords.h - 60.0,
             30.0,
         );
+        let mut stack = VerticalStack::new(column, 5.0);
+        ui.add(Label::new(ctx, "Level info", stack.next(40.0)));
+        let metadata = &self.game.metadata;
+
+        let fields: [(&str, String, usize, TextFieldEvent); 4] = [
+            ("Title", metadata.title.clone(), 32, EditorEvent::SetTitle),
+            (
+                "Author",
+                metadata.author.clone(),
+                32,
+                EditorEvent::SetAuthor,
+            ),
+            (
+                "Description",
+                metadata.description.clone(),
+                64,
+                EditorEvent::SetDescription,
+            ),
+            ("Tags", metadata.tags.join(", "), 64, EditorEvent::SetTags),
+        ];
+        for (name, text, max_length, event) in fields {
+            ui.add(Label::new(ctx, name, stack.next(20.0)));
+            let field = TextField::new(
+                ctx,
+                stack.next(30.0),
+                &text,
+                Box::new(move |text| Some(event(text.to_string()))),
+            )?;
+            ui.add(field.max_length(max_length));
+        }
+
+        ui.add(Label::new(ctx, "Difficulty, 0 for none", stack.next(20.0)));
+        ui.add(NumericStepper::new(
+            ctx,
+            stack.next(30.0),
+            (0.0, 5.0, 1.0),
+            metadata.difficulty.unwrap_or(0) as f32,
+            Box::new(|difficulty| Some(EditorEvent::SetDifficulty(difficulty))),
+        )?);
+
+        ui.add(Label::new(
+            ctx,
+            "Par time in seconds, 0 for none",
+            stack.next(20.0),
+        ));
+        ui.add(NumericStepper::new(
+            ctx,
+            stack.next(30.0),
+            (0.0, 600.0, 5.0),
+            metadata.par_time.unwrap_or(0.0),
+            Box::new(|par_time| Some(EditorEvent::SetParTime(par_time))),
+        )?);
+
+        stack.skip(10.0);
         let on_click: Box<ButtonClickHandler<EditorEvent>> =
-            Box::new(|_| Some(EditorEvent::Preview));
-        Button::new(ctx, bounds, Some("Preview"), on_click)
+            Box::new(|_| Some(EditorEvent::ClosePanel));
+        ui.add(Button::new(ctx, stack.next(40.0), Some("Done"), on_click)?);
+
+        Ok(())
     }
 
-    fn init_save_button(ctx: &mut Context, screen_coords: Rect) -> GameResult<Button<EditorEvent>> {
-        let bounds = Rect::new(
-            screen_coords.x + screen_coords.w - 160.0,
-            screen_coords.y + screen_coords.h - 80.0,
-            150.0,
+    /// A list of all levels to pick one to edit, with a button to go back to the editor
+    fn init_open_dialog(
+        ctx: &mut Context,
+        ui: &mut UiLayer<EditorEvent>,
+        levels: &mut LevelCache,
+        screen_coords: Rect,
+    ) -> GameResult {
+        let column = Anchor::Top.place(
+            screen_coords,
+            screen_coords.w / 2.0,
+            screen_coords.h - 60.0,
             30.0,
         );
+        let mut stack = VerticalStack::new(column, 10.0);
+        ui.add(Label::new(ctx, "Open level", stack.next(40.0)));
+
+        let level_names = Campaign::load().level_order();
+        let mut items = Vec::new();
+        for level_name in &level_names {
+            let item = match levels.get(ctx, level_name)? {
+                Some(level) => ListItem::new(&level_title(level_name, &level.info.metadata))
+                    .details(&format!("levels/{}.json", level_name))
+                    .thumbnail(level.thumbnail.clone()),
+                None => unreadable_level_item(level_name),
+            };
+            items.push(item);
+        }
+        ui.add(ScrollList::new(
+            ctx,
+            stack.next(column.h - 100.0),
+            80.0,
+            items,
+            Box::new(move |_, index| Some(EditorEvent::OpenLevel(level_names[index].clone()))),
+        )?);
+
+        let on_click: Box<ButtonClickHandler<EditorEvent>> =
+            Box::new(|_| Some(EditorEvent::ClosePanel));
+        ui.add(Button::new(
+            ctx,
+            stack.next(40.0),
+            Some("Cancel"),
+            on_click,
+        )?);
+
+        Ok(())
+    }
+
+    fn init_history_buttons(
+        ctx: &mut Context,
+        ui: &mut UiLayer<EditorEvent>,
+        bounds: Rect,
+    ) -> GameResult {
+        let [undo_bounds, redo_bounds] = HorizontalStack::split(bounds, 2, 10.0)[..] else {
+            return Ok(());
+        };
+        let on_click: Box<ButtonClickHandler<EditorEvent>> = Box::new(|_| Some(EditorEvent::Undo));
+        ui.add(Button::new(ctx, undo_bounds, Some("Undo"), on_click)?);
+        let on_click: Box<ButtonClickHandler<EditorEvent>> = Box::new(|_| Some(EditorEvent::Redo));
+        ui.add(Button::new(ctx, redo_bounds, Some("Redo"), on_click)?);
+        Ok(())
+    }
+
+    fn init_save_button(ctx: &mut Context, bounds: Rect) -> GameResult<Button<EditorEvent>> {
         let on_click: Box<ButtonClickHandler<EditorEvent>> = Box::new(|_| Some(EditorEvent::Save));
         Button::new(ctx, bounds, Some("Save"), on_click)
     }
 
-    fn create_obstacle(&mut self) {
-        let obstacle = Polygon::new(vec![
-            Point2::new(100.0, 100.0),
-            Point2::new(200.0, 100.0),
-            Point2::new(200.0, 200.0),
-            Point2::new(100.0, 200.0),
-        ]);
-        self.game.game_map.obstacles.push(obstacle);
+    fn init_level_name_field(
+        &self,
+        ctx: &mut Context,
+        bounds: Rect,
+    ) -> GameResult<TextField<EditorEvent>> {
+        let field = TextField::new(
+            ctx,
+            bounds,
+            &self.level_name,
+            Box::new(|text| Some(EditorEvent::SetLevelName(text.to_string()))),
+        )?;
+        Ok(field.placeholder("Level name").max_length(16))
+    }
+
+    fn init_snap_checkbox(
+        &self,
+        ctx: &mut Context,
+        bounds: Rect,
+    ) -> GameResult<Checkbox<EditorEvent>> {
+        Checkbox::new(
+            ctx,
+            bounds,
+            "Snap to grid",
+            self.always_snap,
+            Box::new(|checked| Some(EditorEvent::SetAlwaysSnap(checked))),
+        )
+    }
+
+    fn init_snap_to_objects_checkbox(
+        &self,
+        ctx: &mut Context,
+        bounds: Rect,
+    ) -> GameResult<Checkbox<EditorEvent>> {
+        Checkbox::new(
+            ctx,
+            bounds,
+            "Snap to objects",
+            self.snap_to_objects,
+            Box::new(|checked| Some(EditorEvent::SetSnapToObjects(checked))),
+        )
+    }
+
+    /// Show and edit the properties of the single selected object
+    fn init_inspector(
+        &self,
+        ctx: &mut Context,
+        ui: &mut UiLayer<EditorEvent>,
+        bounds: Rect,
+        object: SelectionObject,
+    ) -> GameResult {
+        let mut stack = VerticalStack::new(bounds, 10.0);
+        let (title, polygon_type) = match object {
+            SelectionObject::Actor { index } if self.game.actors[index].is_player() => {
+                (String::from("Player"), None)
+            }
+            SelectionObject::Actor { .. } => (String::from("Guard"), None),
+            SelectionObject::GuardPathVertex { vertex_index, .. } => {
+                (format!("Guard waypoint {}", vertex_index + 1), None)
+            }
+            SelectionObject::Polygon { polygon_type } => {
+                (polygon_name(polygon_type).to_string(), Some(polygon_type))
+            }
+            SelectionObject::PolygonVertex {
+                polygon_type,
+                index,
+            } => (
+                format!("{} vertex {}", polygon_name(polygon_type), index + 1),
+                Some(polygon_type),
+            ),
+        };
+        ui.add(Label::new(ctx, &title, stack.next(25.0)));
+
+        if let Some(pos) = self.selection_handler.position(&self.game) {
+            let fields: [(&str, f32, CoordinateEvent); 2] = [
+                ("X", pos.x, EditorEvent::SetPositionX),
+                ("Y", pos.y, EditorEvent::SetPositionY),
+            ];
+            for (axis, value, event) in fields {
+                let mut row = HorizontalStack::new(stack.next(30.0), 10.0);
+                ui.add(Label::new(ctx, axis, row.next(20.0)));
+                ui.add(NumericStepper::new(
+                    ctx,
+                    row.next(bounds.w - 30.0),
+                    (-5000.0, 5000.0, 5.0),
+                    value,
+                    Box::new(move |value| Some(event(value))),
+                )?);
+            }
+        }
+
+        if let Some(PolygonType::LightZone { index }) = polygon_type {
+            ui.add(Label::new(ctx, "Light level", stack.next(20.0)));
+            ui.add(
+                Slider::new(
+                    stack.next(25.0),
+                    0.0,
+                    1.0,
+                    self.game.game_map.light_zones[index].light_level,
+                    Box::new(|level| Some(EditorEvent::SetLightLevel(level))),
+                )
+                .step(0.05),
+            );
+        }
+
+        // The vertices of shapes or the waypoints of guards, which can be picked to edit them
+        let (points, selected) = match object {
+            SelectionObject::Polygon { polygon_type } => {
+                (&polygon_type.get(&self.game.game_map).verts, None)
+            }
+            SelectionObject::PolygonVertex {
+                polygon_type,
+                index,
+            } => (&polygon_type.get(&self.game.game_map).verts, Some(index)),
+            SelectionObject::Actor { index } => match &self.game.actors[index].controller {
+                Controller::Guard(guard) => (&guard.points.verts, None),
+                _ => return Ok(()),
+            },
+            SelectionObject::GuardPathVertex {
+                actor_index,
+                vertex_index,
+            } => match &self.game.actors[actor_index].controller {
+                Controller::Guard(guard) => (&guard.points.verts, Some(vertex_index)),
+                _ => return Ok(()),
+            },
+        };
+        let list_title = if polygon_type.is_some() {
+            "Vertices"
+        } else {
+            "Waypoints"
+        };
+        ui.add(Label::new(ctx, list_title, stack.next(20.0)));
+        let items = points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| ListItem::new(&format!("{}: {:.0}, {:.0}", i + 1, point.x, point.y)))
+            .collect();
+        let list = ScrollList::new(
+            ctx,
+            stack.next(stack.remaining().min(180.0)),
+            30.0,
+            items,
+            Box::new(|_, i| Some(EditorEvent::SelectPoint(i))),
+        )?;
+        ui.add(list.highlight(selected.unwrap_or_default()));
+
+        Ok(())
+    }
+
+    /// Add a label to the left of the row, returning the rest of the row for the widget
+    fn labeled_row(
+        ctx: &mut Context,
+        ui: &mut UiLayer<EditorEvent>,
+        bounds: Rect,
+        label: &str,
+    ) -> Rect {
+        let mut row = HorizontalStack::new(bounds, 10.0);
+        ui.add(Label::new(ctx, label, row.next(LABEL_WIDTH)));
+        row.next(bounds.w - LABEL_WIDTH - 10.0)
+    }
+
+    /// Add widgets for tuning the selected guard, and for what it does at the selected waypoint
+    fn init_guard_panel(
+        &self,
+        ctx: &mut Context,
+        ui: &mut UiLayer<EditorEvent>,
+        bounds: Rect,
+        index: usize,
+        object: SelectionObject,
+    ) -> GameResult {
+        let settings = self.game.actors[index].guard_settings();
+        let mut stack = VerticalStack::new(bounds, 10.0);
+
+        let bounds = Self::labeled_row(ctx, ui, stack.next(25.0), "Speed");
+        ui.add(
+            Slider::new(
+                bounds,
+                0.5,
+                3.0,
+                settings.move_speed,
+                Box::new(|speed| Some(EditorEvent::SetGuardSpeed(speed))),
+            )
+            .step(0.1),
+        );
+
+        let bounds = Self::labeled_row(ctx, ui, stack.next(25.0), "View angle");
+        ui.add(
+            Slider::new(
+                bounds,
+                30.0,
+                180.0,
+                settings.view_angle,
+                Box::new(|angle| Some(EditorEvent::SetGuardViewAngle(angle))),
+            )
+            .step(5.0),
+        );
+
+        let bounds = Self::labeled_row(ctx, ui, stack.next(25.0), "Facing");
+        ui.add(
+            Slider::new(
+                bounds,
+                -180.0,
+                180.0,
+                settings.facing,
+                Box::new(|facing| Some(EditorEvent::SetGuardFacing(facing))),
+            )
+            .step(15.0),
+        );
+
+        let bounds = Self::labeled_row(ctx, ui, stack.next(25.0), "Turn speed");
+        ui.add(
+            Slider::new(
+                bounds,
+                30.0,
+                360.0,
+                settings.turn_speed,
+                Box::new(|speed| Some(EditorEvent::SetGuardTurnSpeed(speed))),
+            )
+            .step(15.0),
+        );
+        ui.add(Checkbox::new(
+            ctx,
+            stack.next(25.0),
+            "Stop to turn",
+            settings.stop_to_turn,
+            Box::new(|checked| Some(EditorEvent::SetGuardStopsToTurn(checked))),
+        )?);
+
+        let bounds = Self::labeled_row(ctx, ui, stack.next(30.0), "View distance");
+        ui.add(NumericStepper::new(
+            ctx,
+            bounds,
+            (100.0, 600.0, 25.0),
+            settings.view_distance,
+            Box::new(|distance| Some(EditorEvent::SetGuardViewDistance(distance))),
+        )?);
+
+        let bounds = Self::labeled_row(ctx, ui, stack.next(30.0), "Patrol");
+        let patrol_dropdown = Dropdown::new(
+            ctx,
+            bounds,
+            PatrolMode::ALL
+                .iter()
+                .map(|mode| mode.name().to_string())
+                .collect(),
+            PatrolMode::ALL
+                .iter()
+                .position(|&mode| mode == settings.patrol_mode)
+                .unwrap_or_default(),
+            Box::new(|i| Some(EditorEvent::SetPatrolMode(PatrolMode::ALL[i]))),
+        )?;
+
+        if let SelectionObject::GuardPathVertex { vertex_index, .. } = object {
+            self.init_waypoint_panel(ctx, ui, &mut stack, index, vertex_index)?;
+        }
+
+        // The patrol mode list opens over the widgets below it, so it is added last
+        ui.add(patrol_dropdown);
+
+        Ok(())
+    }
+
+    /// Add widgets for what the guard does at the selected waypoint
+    fn init_waypoint_panel(
+        &self,
+        ctx: &mut Context,
+        ui: &mut UiLayer<EditorEvent>,
+        stack: &mut VerticalStack,
+        actor_index: usize,
+        vertex_index: usize,
+    ) -> GameResult {
+        let settings = self.game.actors[actor_index].guard_settings();
+        let waypoint = settings
+            .waypoints
+            .get(vertex_index)
+            .cloned()
+            .unwrap_or_default();
+
+        let bounds = Self::labeled_row(ctx, ui, stack.next(30.0), "Wait");
+        ui.add(NumericStepper::new(
+            ctx,
+            bounds,
+            (0.0, 30.0, 0.5),
+            waypoint.wait,
+            Box::new(|wait| Some(EditorEvent::SetWaypointWait(wait))),
+        )?);
+
+        ui.add(Label::new(ctx, "Look while waiting", stack.next(20.0)));
+        for (i, &look) in waypoint.looks.iter().enumerate() {
+            let row_bounds = stack.next(25.0);
+            let mut row = HorizontalStack::new(row_bounds, 10.0);
+            ui.add(
+                Slider::new(
+                    row.next(row_bounds.w - 40.0),
+                    -180.0,
+                    180.0,
+                    look,
+                    Box::new(move |look| Some(EditorEvent::SetWaypointLook(i, look))),
+                )
+                .step(15.0),
+            );
+            let on_click: Box<ButtonClickHandler<EditorEvent>> =
+                Box::new(move |_| Some(EditorEvent::RemoveWaypointLook(i)));
+            ui.add(Button::new(ctx, row.next(30.0), Some("-"), on_click)?);
+        }
+        let on_click: Box<ButtonClickHandler<EditorEvent>> =
+            Box::new(|_| Some(EditorEvent::AddWaypointLook));
+        let add_button = Button::new(ctx, stack.next(30.0), Some("Add look"), on_click)?;
+        ui.add(add_button.disabled(waypoint.looks.len() >= MAX_LOOKS));
+
+        Ok(())
+    }
+
+    /// Add buttons for transforming everything that is selected
+    fn init_selection_panel(
+        ctx: &mut Context,
+        ui: &mut UiLayer<EditorEvent>,
+        stack: &mut VerticalStack,
+    ) -> GameResult {
+        let rows = [
+            [
+                ("Rotate -", Transform::RotateLeft),
+                ("Rotate +", Transform::RotateRight),
+            ],
+            [
+                ("Grow", Transform::ScaleUp),
+                ("Shrink", Transform::ScaleDown),
+            ],
+            [
+                ("Flip H", Transform::MirrorHorizontal),
+                ("Flip V", Transform::MirrorVertical),
+            ],
+        ];
+        for row in rows {
+            let bounds = HorizontalStack::split(stack.next(30.0), row.len(), 10.0);
+            for ((text, transform), bounds) in row.into_iter().zip(bounds) {
+                let on_click: Box<ButtonClickHandler<EditorEvent>> =
+                    Box::new(move |_| Some(EditorEvent::TransformSelection(transform)));
+                ui.add(Button::new(ctx, bounds, Some(text), on_click)?);
+            }
+        }
+
+        let on_click: Box<ButtonClickHandler<EditorEvent>> =
+            Box::new(|_| Some(EditorEvent::DeleteSelection));
+        ui.add(Button::new(
+            ctx,
+            stack.next(30.0),
+            Some("Delete"),
+            on_click,
+        )?);
+
+        Ok(())
+    }
+
+    /// Index of the selected guard, or of the guard whose waypoint is selected
+    fn selected_guard(&self) -> Option<usize> {
+        match self.selection_handler.single() {
+            Some(
+                SelectionObject::Actor { index }
+                | SelectionObject::GuardPathVertex {
+                    actor_index: index, ..
+                },
+            ) => self
+                .game
+                .actors
+                .get(index)
+                .filter(|actor| !actor.is_player())
+                .map(|_| index),
+            _ => None,
+        }
+    }
+
+    /// Change the settings of the selected guard
+    fn update_selected_guard(&mut self, update: impl Fn(&mut GuardSettings)) {
+        if let Some(index) = self.selected_guard() {
+            let guard = &mut self.game.actors[index];
+            let mut settings = guard.guard_settings();
+            update(&mut settings);
+            guard.apply_guard_settings(&settings);
+        }
+    }
+
+    /// Change what the guard does at the selected waypoint, keeping no settings for the
+    /// waypoints at the end of the path where it does not stop
+    fn update_selected_waypoint(&mut self, update: impl Fn(&mut WaypointSettings)) {
+        let Some(SelectionObject::GuardPathVertex { vertex_index, .. }) =
+            self.selection_handler.single()
+        else {
+            return;
+        };
+        self.update_selected_guard(|settings| {
+            let waypoints = &mut settings.waypoints;
+            if waypoints.len() <= vertex_index {
+                waypoints.resize(vertex_index + 1, WaypointSettings::default());
+            }
+            update(&mut waypoints[vertex_index]);
+            while waypoints.last() == Some(&WaypointSettings::default()) {
+                waypoints.pop();
+            }
+        });
+    }
+
+    /// Replace the level with an undone or redone version of it
+    fn restore(&mut self, level_info: LevelInfo) {
+        self.game = Game::from_level_info(level_info);
+        self.history.restored(&self.game);
+        self.selection_handler = SelectionHandler::new();
+        self.drawing = None;
+        self.ui = None;
+    }
+
+    /// Start drawing a shape, returning how to draw it
+    fn start_drawing(&mut self, target: DrawTarget) -> ViewEvent {
+        let cursor = self.snap(self.mouse_pos);
+        self.drawing = Some(ShapeDrawing::new(
+            target,
+            self.shape,
+            self.circle_sides,
+            cursor,
+        ));
+        self.selection_handler.selection.clear();
+        ViewEvent::ShowToast(self.shape.instructions().to_string())
     }
 
     fn create_guard(&mut self) {
@@ -148,62 +931,327 @@ impl EditorView {
         self.game.actors.push(actor);
     }
 
-    fn delete_selected_object(&mut self) {
-        match &self.selection_handler.selected_object {
-            Some(obj) => match obj {
-                SelectionObject::Actor { index } if *index != 0 => {
-                    self.game.actors.remove(*index);
-                    self.selection_handler.selected_object = None;
-                }
-                SelectionObject::Polygon {
-                    polygon_type: PolygonType::Obstacle { index },
-                } => {
-                    self.game.game_map.obstacles.remove(*index);
-                    self.selection_handler.selected_object = None;
-                }
-                _ => (),
-            },
-            None => (),
+    /// Snap a point to the grid while snapping is on
+    fn snap(&self, point: Point2<f32>) -> Point2<f32> {
+        if self.snap_to_grid || self.always_snap {
+            snap_to_grid(point)
+        } else {
+            point
+        }
+    }
+
+    /// Add the objects centered at `pos` and select them
+    fn paste(&mut self, copied: &CopiedObjects, pos: Point2<f32>) {
+        let Some(center) = copied.center() else {
+            return;
+        };
+
+        // Move by whole grid steps while snapping, so objects on the grid stay on it
+        let mut offset = pos - center;
+        if self.snap_to_grid || self.always_snap {
+            offset = snap_to_grid(offset.into()).coords;
         }
+
+        let added = copied.add_to(&mut self.game, offset);
+        self.selection_handler.selection = added;
     }
 
-    fn handle_editor_events(&mut self, events: Vec<EditorEvent>) -> Vec<ViewEvent> {
+    /// Write the level to the levels directory, returning the name of the file without extension
+    fn save_level(&self) -> io::Result<String> {
+        let level_info = self.game.to_level_info();
+
+        // Overwrite the opened level, or find a file name that is not taken yet
+        let stem = level_file_stem(&self.level_name);
+        let overwrite = self.opened_level.as_ref() == Some(&stem);
+        let mut i = 1;
+        let (file_stem, path) = loop {
+            let file_stem = match (stem.is_empty(), i) {
+                (true, _) => format!("level_{}", i),
+                (false, 1) => stem.clone(),
+                (false, _) => format!("{}_{}", stem, i),
+            };
+            let mut path = Path::new("levels").join(&file_stem);
+            path.set_extension("json");
+            if overwrite || !path.exists() {
+                break (file_stem, path);
+            }
+            i += 1
+        };
+
+        // Create the file and write the data
+        let file = File::create(path)?;
+        serde_json::to_writer(file, &level_info)?;
+
+        Ok(file_stem)
+    }
+
+    /// The event of the Ctrl shortcut bound to the key
+    fn shortcut(&self, key_code: KeyCode) -> Option<EditorEvent> {
+        let action = Action::ALL
+            .into_iter()
+            .find(|&action| action.needs_ctrl() && self.controls.matches_key(action, key_code))?;
+        match action {
+            Action::EditorCopy => Some(EditorEvent::Copy),
+            Action::EditorPaste => Some(EditorEvent::Paste),
+            Action::EditorDuplicate => Some(EditorEvent::Duplicate),
+            Action::EditorUndo => Some(EditorEvent::Undo),
+            Action::EditorRedo => Some(EditorEvent::Redo),
+            _ => None,
+        }
+    }
+
+    /// Panels take all input, so the level below them can not be changed
+    fn panel_input(
+        ctx: &mut Context,
+        ui: &mut UiLayer<EditorEvent>,
+        input: Input,
+    ) -> Vec<EditorEvent> {
+        match input {
+            Input::MouseDown { button, x, y } => ui.mouse_press(ctx, button, x, y),
+            Input::MouseMotion { x, y } => ui.mouse_motion(ctx, x, y),
+            Input::MouseUp { x, y, .. } => ui.mouse_release(ctx, x, y),
+            Input::MouseWheel { x, y, delta } => {
+                ui.mouse_wheel(ctx, x, y, delta);
+                Vec::new()
+            }
+            Input::KeyDown {
+                key_code: KeyCode::Escape,
+            } => vec![EditorEvent::ClosePanel],
+            Input::CharTyped { character } => ui.text_input(ctx, character),
+            Input::KeyDown { key_code } => ui.key_press(ctx, key_code),
+            _ => Vec::new(),
+        }
+    }
+
+    fn handle_editor_events(&mut self, ctx: &Context, events: Vec<EditorEvent>) -> Vec<ViewEvent> {
         let mut view_events = Vec::new();
 
         for event in events {
             match event {
-                EditorEvent::CreateObstacle => self.create_obstacle(),
+                EditorEvent::StartDrawing(target) => view_events.push(self.start_drawing(target)),
+                EditorEvent::AddShape(verts) => {
+                    if let Some(drawing) = self.drawing.take() {
+                        let polygon_type = drawing.target.add_to(&mut self.game.game_map, verts);
+                        self.selection_handler.selection =
+                            vec![SelectionObject::Polygon { polygon_type }];
+                    }
+                }
+                EditorEvent::SetShape(shape) => {
+                    self.shape = shape;
+                    self.ui = None;
+                }
+                EditorEvent::SetCircleSides(sides) => self.circle_sides = sides as usize,
                 EditorEvent::CreateGuard => self.create_guard(),
                 EditorEvent::ViewEvent(view_event) => view_events.push(view_event),
                 EditorEvent::Preview => {
                     let level_info = self.game.to_level_info();
-                    let view = Box::new(GameView::new(level_info));
+                    let view = Box::new(GameView::new(ctx, level_info, None));
                     let view_event = ViewEvent::PushView(view);
                     view_events.push(view_event)
                 }
-                EditorEvent::Save => {
-                    // Make the LevelInfo
-                    let level_info = self.game.to_level_info();
-
-                    // Find a file name of the form level_x.json
-                    let mut i = 1;
-                    let mut path: PathBuf;
-                    loop {
-                        path = Path::new("levels").join(format!("level_{}", i));
-                        path.set_extension("json");
-                        if !path.exists() {
-                            break;
+                EditorEvent::ShowPanel(panel) => {
+                    self.panel = Some(panel);
+                    self.ui = None;
+                }
+                EditorEvent::ClosePanel => {
+                    self.panel = None;
+                    self.ui = None;
+                }
+                EditorEvent::OpenLevel(level_name) => {
+                    let level_info = match load_level_info(&level_name) {
+                        Ok(level_info) => level_info,
+                        Err(e) => {
+                            let dialog =
+                                DialogView::message("Could not open level", &e.to_string());
+                            view_events.push(ViewEvent::PushView(Box::new(dialog)));
+                            continue;
                         }
-                        i += 1
+                    };
+                    self.game = Game::from_level_info(level_info);
+                    self.selection_handler = SelectionHandler::new();
+                    self.drawing = None;
+                    self.history = History::new(&self.game);
+                    self.level_name = level_name.clone();
+                    self.opened_level = Some(level_name);
+                    self.panel = None;
+                    self.ui = None;
+                }
+                EditorEvent::SetLevelName(name) => self.level_name = name,
+                EditorEvent::SetTitle(title) => self.game.metadata.title = title,
+                EditorEvent::SetAuthor(author) => self.game.metadata.author = author,
+                EditorEvent::SetDescription(description) => {
+                    self.game.metadata.description = description
+                }
+                EditorEvent::SetTags(tags) => {
+                    self.game.metadata.tags = LevelMetadata::parse_tags(&tags)
+                }
+                // Zero means the level has no difficulty or par time
+                EditorEvent::SetDifficulty(difficulty) => {
+                    self.game.metadata.difficulty = (difficulty > 0.0).then_some(difficulty as u8)
+                }
+                EditorEvent::SetParTime(par_time) => {
+                    self.game.metadata.par_time = (par_time > 0.0).then_some(par_time)
+                }
+                EditorEvent::SetAlwaysSnap(checked) => self.always_snap = checked,
+                EditorEvent::SetSnapToObjects(checked) => self.snap_to_objects = checked,
+                EditorEvent::SetGuardSpeed(speed) => {
+                    self.update_selected_guard(|settings| settings.move_speed = speed)
+                }
+                EditorEvent::SetGuardViewAngle(angle) => {
+                    self.update_selected_guard(|settings| settings.view_angle = angle)
+                }
+                EditorEvent::SetGuardViewDistance(distance) => {
+                    self.update_selected_guard(|settings| settings.view_distance = distance)
+                }
+                EditorEvent::SetGuardFacing(facing) => {
+                    self.update_selected_guard(|settings| settings.facing = facing)
+                }
+                EditorEvent::SetPatrolMode(mode) => {
+                    self.update_selected_guard(|settings| settings.patrol_mode = mode)
+                }
+                EditorEvent::SetWaypointWait(wait) => {
+                    self.update_selected_waypoint(|waypoint| waypoint.wait = wait)
+                }
+                EditorEvent::SetGuardTurnSpeed(speed) => {
+                    self.update_selected_guard(|settings| settings.turn_speed = speed)
+                }
+                EditorEvent::SetGuardStopsToTurn(checked) => {
+                    self.update_selected_guard(|settings| settings.stop_to_turn = checked)
+                }
+                // New looks start where the last one ended, or where the guard faces at the
+                // start of the level
+                EditorEvent::AddWaypointLook => {
+                    let facing = self
+                        .selected_guard()
+                        .map(|index| self.game.actors[index].guard_settings().facing)
+                        .unwrap_or_default();
+                    self.update_selected_waypoint(|waypoint| {
+                        let look = waypoint.looks.last().copied().unwrap_or(facing);
+                        waypoint.looks.push(look);
+                    });
+                    self.ui = None;
+                }
+                EditorEvent::RemoveWaypointLook(i) => {
+                    self.update_selected_waypoint(|waypoint| {
+                        if i < waypoint.looks.len() {
+                            waypoint.looks.remove(i);
+                        }
+                    });
+                    self.ui = None;
+                }
+                EditorEvent::SetWaypointLook(i, look) => {
+                    self.update_selected_waypoint(|waypoint| {
+                        if let Some(old) = waypoint.looks.get_mut(i) {
+                            *old = look;
+                        }
+                    })
+                }
+                EditorEvent::SetLightLevel(level) => {
+                    if let Some(
+                        SelectionObject::Polygon {
+                            polygon_type: PolygonType::LightZone { index },
+                        }
+                        | SelectionObject::PolygonVertex {
+                            polygon_type: PolygonType::LightZone { index },
+                            ..
+                        },
+                    ) = self.selection_handler.single()
+                    {
+                        self.game.game_map.light_zones[index].light_level = level
+                    }
+                }
+                EditorEvent::SetPositionX(x) => {
+                    if let Some(pos) = self.selection_handler.position(&self.game) {
+                        let pos = Point2::new(x, pos.y);
+                        self.selection_handler.set_position(&mut self.game, pos);
+                    }
+                }
+                EditorEvent::SetPositionY(y) => {
+                    if let Some(pos) = self.selection_handler.position(&self.game) {
+                        let pos = Point2::new(pos.x, y);
+                        self.selection_handler.set_position(&mut self.game, pos);
+                    }
+                }
+                EditorEvent::SelectPoint(i) => {
+                    let point = match self.selection_handler.single() {
+                        Some(
+                            SelectionObject::Actor { index: actor_index }
+                            | SelectionObject::GuardPathVertex { actor_index, .. },
+                        ) => Some(SelectionObject::GuardPathVertex {
+                            actor_index,
+                            vertex_index: i,
+                        }),
+                        Some(
+                            SelectionObject::Polygon { polygon_type }
+                            | SelectionObject::PolygonVertex { polygon_type, .. },
+                        ) => Some(SelectionObject::PolygonVertex {
+                            polygon_type,
+                            index: i,
+                        }),
+                        None => None,
+                    };
+                    self.selection_handler.selection = point.into_iter().collect();
+                }
+                EditorEvent::Undo => {
+                    // Changes that were not committed yet, like edits in a panel, are undone first
+                    self.history.commit(&self.game);
+                    if let Some(level_info) = self.history.undo() {
+                        self.restore(level_info);
+                    }
+                }
+                EditorEvent::Redo => {
+                    if let Some(level_info) = self.history.redo() {
+                        self.restore(level_info);
+                    }
+                }
+                EditorEvent::TransformSelection(transform) => {
+                    let snap = self.snap_to_grid || self.always_snap;
+                    self.selection_handler
+                        .transform_selection(&mut self.game, transform, snap);
+                }
+                EditorEvent::DeleteSelection => {
+                    self.selection_handler.delete_selection(&mut self.game)
+                }
+                EditorEvent::Copy => {
+                    let copied = CopiedObjects::from_selection(
+                        &self.game,
+                        &self.selection_handler.selection,
+                    );
+                    if !copied.is_empty() {
+                        clipboard::set_text(&copied.to_json());
+                    }
+                }
+                EditorEvent::Paste => match CopiedObjects::from_json(&clipboard::get_text()) {
+                    Some(copied) => self.paste(&copied, self.mouse_pos),
+                    None => view_events.push(ViewEvent::ShowToast(String::from(
+                        "There are no level objects on the clipboard",
+                    ))),
+                },
+                EditorEvent::Duplicate => {
+                    let copied = CopiedObjects::from_selection(
+                        &self.game,
+                        &self.selection_handler.selection,
+                    );
+                    // Place the copy one grid step away from the original
+                    if let Some(center) = copied.center() {
+                        self.paste(&copied, center + Vector2::new(GRID_SIZE, GRID_SIZE));
                     }
-
-                    // Create the file and write the data
-                    let file = File::create(path).expect("Error while creating level file");
-                    serde_json::to_writer(file, &level_info).expect("Error while saving level");
-
-                    // Exit the editor
-                    view_events.push(ViewEvent::PopView);
                 }
+                EditorEvent::Save => match self.save_level() {
+                    Ok(file_stem) => {
+                        let message = format!("Level saved to levels/{}.json", file_stem);
+                        view_events.push(ViewEvent::ShowToast(message));
+                        self.levels.remove(&file_stem);
+                        // Saving again overwrites the same file
+                        self.level_name = file_stem.clone();
+                        self.opened_level = Some(file_stem);
+                        self.ui = None;
+                    }
+                    Err(e) => {
+                        let dialog = DialogView::message("Could not save level", &e.to_string());
+                        view_events.push(ViewEvent::PushView(Box::new(dialog)));
+                    }
+                },
             }
         }
 
@@ -219,6 +1267,12 @@ impl View for EditorView {
     fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult<()> {
         self.renderer
             .render(ctx, canvas, &self.game, Some(&self.selection_handler))?;
+        if let Some(drawing) = &self.drawing {
+            self.renderer.draw_shape_drawing(ctx, canvas, drawing)?;
+        }
+        if let Some(guide) = self.snap_guide {
+            self.renderer.draw_snap_guide(ctx, canvas, guide)?;
+        }
 
         if self.ui.is_none() {
             self.init_ui(ctx, canvas)?;
@@ -227,55 +1281,199 @@ impl View for EditorView {
         self.ui.as_ref().unwrap().draw(ctx, canvas)
     }
 
+    fn resize(&mut self, _ctx: &mut Context) {
+        self.ui = None;
+    }
+
     fn receive_input(&mut self, ctx: &mut Context, input: Input) -> Vec<ViewEvent> {
         let mut events = vec![];
+        let inspected = self.selection_handler.single();
+        let is_motion = matches!(input, Input::MouseMotion { .. });
+        let has_selection = !self.selection_handler.selection.is_empty();
+        let snapping = self.snap_to_grid || self.always_snap;
+        let snap = |point| if snapping { snap_to_grid(point) } else { point };
+        let mut shortcut = match input {
+            Input::KeyDown { key_code }
+                if ctx
+                    .keyboard
+                    .active_mods()
+                    .intersects(KeyMods::CTRL | KeyMods::LOGO) =>
+            {
+                self.shortcut(key_code)
+            }
+            _ => None,
+        };
 
-        match &mut self.ui {
-            Some(ui) => match input {
+        if let Some(ui) = &mut self.ui {
+            if self.panel.is_some() {
+                events.extend(Self::panel_input(ctx, ui, input));
+                return self.handle_editor_events(ctx, events);
+            }
+
+            match input {
                 Input::MouseDown { button, x, y } => {
-                    let pos = Point2::new(x, y);
-                    self.selection_handler
-                        .handle_mouse_down(&mut self.game, button, pos);
+                    // Clicks on the UI should not select or drag the objects below it
+                    self.pressing_ui = ui.contains_point(ctx, x, y);
+                    if let (false, Some(drawing)) = (self.pressing_ui, &mut self.drawing) {
+                        // Right clicks undo the last point, or stop drawing if there is none
+                        if button == MouseButton::Right {
+                            if !drawing.remove_last_point() {
+                                self.drawing = None;
+                            }
+                        } else {
+                            // Click where the cursor snapped to an object
+                            let pos = match self.snap_guide {
+                                Some(_) => drawing.cursor(),
+                                None => snap(Point2::new(x, y)),
+                            };
+                            events.extend(drawing.click(pos).map(EditorEvent::AddShape));
+                        }
+                    } else if !self.pressing_ui {
+                        let pos = Point2::new(x, y);
+                        let add = ctx.keyboard.active_mods().contains(KeyMods::SHIFT);
+                        self.selection_handler
+                            .handle_mouse_down(&mut self.game, button, pos, add);
+                    }
 
                     events.extend(ui.mouse_press(ctx, button, x, y));
                 }
                 Input::MouseMotion { x, y } => {
                     let mouse_pos = Point2::new(x, y);
+                    self.mouse_pos = mouse_pos;
+                    events.extend(ui.mouse_motion(ctx, x, y));
 
-                    self.selection_handler.handle_mouse_motion(
-                        &mut self.game,
-                        if self.snap_to_grid {
-                            snap_to_grid(mouse_pos)
-                        } else {
-                            mouse_pos
+                    let mut pos = snap(mouse_pos);
+
+                    // Points snapped to objects leave the grid
+                    let dragged_point = match &self.drawing {
+                        Some(drawing) => Some((SnapSource::New, drawing.points().last().copied())),
+                        None => self.selection_handler.dragged_point(&self.game),
+                    };
+                    let object_snap = dragged_point.filter(|_| self.snap_to_objects).and_then(
+                        |(source, previous)| {
+                            snapping::snap_point(&self.game, mouse_pos, source, previous)
                         },
                     );
+                    self.snap_guide = object_snap.map(|snap| snap.guide);
+                    if let Some(snap) = object_snap {
+                        pos = snap.point;
+                    }
+                    match &mut self.drawing {
+                        Some(drawing) => drawing.move_cursor(pos),
+                        None => self
+                            .selection_handler
+                            .handle_mouse_motion(&mut self.game, pos),
+                    }
+                }
+                Input::MouseUp { button, x, y } => {
+                    events.extend(ui.mouse_release(ctx, x, y));
+                    if !self.pressing_ui {
+                        self.selection_handler
+                            .handle_mouse_up(&mut self.game, button)
+                    }
+                    self.pressing_ui = false;
+                }
+                // Typing a level name should not trigger the editor shortcuts
+                Input::KeyDown { key_code } if ui.has_text_focus() => {
+                    events.extend(ui.key_press(ctx, key_code));
+                }
+                Input::KeyDown { key_code }
+                    if self.drawing.is_some()
+                        && matches!(
+                            key_code,
+                            KeyCode::Return
+                                | KeyCode::NumpadEnter
+                                | KeyCode::Escape
+                                | KeyCode::Back
+                        ) =>
+                {
+                    let drawing = self.drawing.as_mut().unwrap();
+                    match key_code {
+                        KeyCode::Escape => self.drawing = None,
+                        KeyCode::Back => {
+                            drawing.remove_last_point();
+                        }
+                        _ => events.extend(drawing.finish().map(EditorEvent::AddShape)),
+                    }
+                }
+                Input::KeyDown { .. } if shortcut.is_some() => events.extend(shortcut.take()),
+                Input::KeyDown { key_code } => {
+                    if self.controls.matches_key(Action::EditorExit, key_code) {
+                        let dialog = DialogView::confirm(
+                            "Leave the editor?",
+                            "Changes that are not saved will be lost.",
+                            "Leave",
+                            Box::new(|_| vec![ViewEvent::PopView]),
+                        );
+                        let view_event = ViewEvent::PushView(Box::new(dialog));
+                        events.push(EditorEvent::ViewEvent(view_event));
+                    } else if self.controls.matches_key(Action::EditorSnap, key_code) {
+                        self.snap_to_grid = true;
+                    } else if self
+                        .controls
+                        .matches_key(Action::EditorCreateObstacle, key_code)
+                    {
+                        events.push(EditorEvent::StartDrawing(DrawTarget::Obstacle));
+                    } else if self.controls.matches_key(Action::EditorDelete, key_code) {
+                        events.push(EditorEvent::DeleteSelection);
+                    } else {
+                        events.extend(ui.key_press(ctx, key_code));
+                    }
+                }
+                Input::CharTyped { character } => events.extend(ui.text_input(ctx, character)),
+                Input::MouseWheel { x, y, delta } => ui.mouse_wheel(ctx, x, y, delta),
+                Input::KeyUp { key_code }
+                    if self.controls.matches_key(Action::EditorSnap, key_code) =>
+                {
+                    self.snap_to_grid = false
                 }
-                Input::MouseUp { button, .. } => self
-                    .selection_handler
-                    .handle_mouse_up(&mut self.game, button),
-                Input::KeyDown { key_code } => match key_code {
-                    KeyCode::Escape => events.push(EditorEvent::ViewEvent(ViewEvent::PopView)),
-                    KeyCode::LControl => self.snap_to_grid = true,
-                    KeyCode::O => self.create_obstacle(),
-                    KeyCode::Delete => self.delete_selected_object(),
-                    _ => {}
-                },
-                Input::KeyUp {
-                    key_code: KeyCode::LControl,
-                } => self.snap_to_grid = false,
                 _ => {}
-            },
-            None => {}
-        };
+            }
+        }
+
+        let edits_properties = events.iter().any(EditorEvent::edits_properties);
+        let view_events = self.handle_editor_events(ctx, events);
+
+        // Remember changes when they are done, rather than on every step of a drag
+        let changed = !is_motion
+            && !self.pressing_ui
+            && !self.selection_handler.is_dragging()
+            && self.history.commit(&self.game);
+
+        // Guides are only shown while a point is placed or dragged
+        if self.drawing.is_none() && self.selection_handler.dragged_point(&self.game).is_none() {
+            self.snap_guide = None;
+        }
+
+        // Show or hide the inspector and transform buttons when the selection changes, and show
+        // the new values after changes made outside of the inspector
+        if self.selection_handler.single() != inspected
+            || self.selection_handler.selection.is_empty() == has_selection
+            || (changed && !edits_properties)
+        {
+            self.ui = None;
+        }
 
-        self.handle_editor_events(events)
+        view_events
+    }
+
+    fn resume(&mut self, ctx: &mut Context) {
+        self.controls = Controls::load(ctx);
+    }
+}
+
+/// What a kind of shape is called in the inspector
+fn polygon_name(polygon_type: PolygonType) -> &'static str {
+    match polygon_type {
+        PolygonType::Obstacle { .. } => "Obstacle",
+        PolygonType::LightZone { .. } => "Dark zone",
+        PolygonType::EndArea { .. } => "End area",
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::view::editor::snap_to_grid;
+    use crate::view::editor::{level_file_stem, snap_to_grid};
     use nalgebra::Point2;
 
     #[test]
@@ -293,4 +1491,10 @@ mod tests {
         let expected = Point2::new(50.0, 50.0);
         assert_eq!(expected, snapped_point)
     }
+
+    #[test]
+    fn level_file_stem_is_sanitized() {
+        assert_eq!(level_file_stem(" The Vault 2! "), "the_vault_2_");
+        assert_eq!(level_file_stem("   "), "");
+    }
 }
@@ -1,54 +1,122 @@
+use super::{levels::level_title, pause::PauseView, result::ResultView};
+use crate::campaign::save_data::SaveData;
+use crate::controls::{Action, Controls};
 use crate::game::{level_info::LevelInfo, rendering::Renderer, Game};
+use crate::game::{stats::RunStats, Outcome};
+use crate::preferences::Preferences;
 use crate::{
     state::Input,
     view::{View, ViewEvent},
 };
-use ggez::{graphics::Canvas, input::keyboard::KeyCode, Context, GameResult};
-use std::{fs::File, path::Path};
+use ggez::{graphics::Canvas, Context, GameResult};
+use std::{fs::File, io, path::Path};
 
 pub struct GameView {
     game: Game,
     renderer: Renderer,
+    level_info: LevelInfo,
+    level_name: Option<String>,
+    display_name: String,
 }
 
 impl GameView {
-    pub fn new(level_info: LevelInfo) -> Self {
+    /// Create a view playing the given level, where `level_name` is the name of the level file
+    /// if the level was loaded from one
+    pub fn new(ctx: &Context, level_info: LevelInfo, level_name: Option<String>) -> Self {
+        let display_name = match &level_name {
+            Some(name) => level_title(name, &level_info.metadata),
+            None => String::from("Preview"),
+        };
+
+        let mut game = Game::from_level_info(level_info.clone());
+        game.controls = Controls::load(ctx);
+        game.detection.base_rate *= Preferences::load(ctx).difficulty.detection_multiplier();
+
         GameView {
-            game: Game::from_level_info(level_info),
+            game,
             renderer: Renderer::new(),
+            level_info,
+            level_name,
+            display_name,
         }
     }
 }
 
 impl View for GameView {
     fn tick(&mut self, ctx: &mut Context) -> Vec<ViewEvent> {
-        self.game.tick(ctx)
+        match self.game.tick(ctx) {
+            Some(outcome) => {
+                let stats = self.game.run_stats();
+                if let (Outcome::Won, Some(level_name)) = (outcome, &self.level_name) {
+                    record_completion(ctx, level_name, &stats);
+                }
+
+                let view = ResultView::new(
+                    ctx,
+                    outcome,
+                    stats,
+                    self.level_info.clone(),
+                    self.level_name.clone(),
+                );
+                vec![ViewEvent::Transition(vec![ViewEvent::ReplaceView(
+                    Box::new(view),
+                )])]
+            }
+            None => Vec::new(),
+        }
     }
 
     fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
-        self.renderer.render(ctx, canvas, &self.game, None)
+        self.renderer.render(ctx, canvas, &self.game, None)?;
+        self.renderer
+            .draw_objective_list(canvas, &self.game.objectives);
+        self.renderer
+            .draw_hud(ctx, canvas, &self.game, &self.display_name)?;
+
+        Ok(())
     }
 
     fn receive_input(&mut self, _ctx: &mut Context, input: Input) -> Vec<ViewEvent> {
         let mut events = Vec::new();
 
-        if let Input::KeyDown {
-            key_code: KeyCode::Escape,
-        } = input
-        {
-            events.push(ViewEvent::PopView)
+        let pause = match input {
+            Input::KeyDown { key_code } => self.game.controls.matches_key(Action::Pause, key_code),
+            Input::GamepadButtonDown { button } => {
+                self.game.controls.matches_button(Action::Pause, button)
+            }
+            _ => false,
+        };
+
+        if pause {
+            let view = PauseView::new(
+                self.level_info.clone(),
+                self.level_name.clone(),
+                self.game.controls.clone(),
+            );
+            events.push(ViewEvent::PushView(Box::new(view)))
         };
 
         events
     }
+
+    fn resume(&mut self, ctx: &mut Context) {
+        // The controls may have been changed from the pause menu
+        self.game.controls = Controls::load(ctx);
+    }
 }
 
-pub fn load_level_info(level_name: &str) -> LevelInfo {
+fn record_completion(ctx: &Context, level_name: &str, stats: &RunStats) {
+    let mut save_data = SaveData::load(ctx);
+    save_data.record_completion(level_name, stats);
+    if let Err(e) = save_data.save(ctx) {
+        eprintln!("Could not save progress: {}", e);
+    }
+}
+
+pub fn load_level_info(level_name: &str) -> io::Result<LevelInfo> {
     let mut path = Path::new("levels").join(level_name);
     path.set_extension("json");
 
-    match File::open(path) {
-        Ok(file) => serde_json::from_reader(file).unwrap(),
-        Err(e) => panic!("Could not read level file: {}", e),
-    }
+    let file = File::open(path)?;
+    serde_json::from_reader(file).map_err(io::Error::from)
 }
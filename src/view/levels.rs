@@ -3,27 +3,46 @@ use super::{
     View, ViewEvent,
 };
 use crate::{
-    gui::{button::Button, UiLayer},
+    campaign::{save_data::SaveData, Campaign},
+    controls::{Action, Controls},
+    game::{
+        level_info::{LevelInfo, LevelMetadata},
+        rendering::Renderer,
+        Game,
+    },
+    gui::{
+        label::Label,
+        layout::{Anchor, VerticalStack},
+        scroll_list::{ListItem, ScrollList},
+        UiLayer,
+    },
     state::Input,
 };
 use ggez::{
-    graphics::{Canvas, Rect},
+    graphics::{Canvas, Color, Image, Rect},
     input::keyboard::KeyCode,
     Context, GameResult,
 };
-use std::{fs, path::Path};
+use std::collections::HashMap;
+
+/// Width and height of level thumbnails in pixels
+const THUMBNAIL_SIZE: u32 = 128;
 
 pub struct LevelsView {
-    level_names: Vec<String>,
+    campaign: Campaign,
+    save_data: SaveData,
+    levels: LevelCache,
+    controls: Controls,
     ui_layer: Option<UiLayer<ViewEvent>>,
 }
 
 impl LevelsView {
-    pub fn new(_ctx: &mut Context) -> GameResult<Self> {
-        let level_names = get_all_level_names();
-
+    pub fn new(ctx: &mut Context) -> GameResult<Self> {
         Ok(LevelsView {
-            level_names,
+            campaign: Campaign::load(),
+            save_data: SaveData::load(ctx),
+            levels: LevelCache::new(),
+            controls: Controls::load(ctx),
             ui_layer: None,
         })
     }
@@ -31,20 +50,60 @@ impl LevelsView {
     fn init_ui(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
         let mut ui_layer = UiLayer::new();
 
-        let mut y = 30.0;
-
         let screen_coords = canvas.screen_coordinates().unwrap();
-        for level_name in &self.level_names {
-            let bounds = Rect::new(
-                screen_coords.x + screen_coords.w / 4.0,
-                screen_coords.y + y,
-                screen_coords.w / 2.0,
-                60.0,
-            );
-            ui_layer.add(level_button(ctx, bounds, level_name.to_owned())?);
-            y += 70.0;
+        let column = Anchor::Top.place(
+            screen_coords,
+            screen_coords.w / 2.0,
+            screen_coords.h - 60.0,
+            30.0,
+        );
+        let mut stack = VerticalStack::new(column, 10.0);
+        ui_layer.add(Label::new(ctx, "Levels", stack.next(40.0)));
+
+        let level_names = self.campaign.level_order();
+        let mut items = Vec::new();
+        for level_name in &level_names {
+            let unlocked = self.campaign.is_unlocked(level_name, &self.save_data);
+            let item = match self.levels.get(ctx, level_name)? {
+                Some(level) => {
+                    let metadata = &level.info.metadata;
+                    ListItem::new(&level_title(level_name, metadata))
+                        .details(&level_details(
+                            level_name,
+                            metadata,
+                            unlocked,
+                            &self.save_data,
+                        ))
+                        .description(&metadata.description)
+                        .thumbnail(level.thumbnail.clone())
+                        .disabled(!unlocked)
+                }
+                None => unreadable_level_item(level_name),
+            };
+            items.push(item);
         }
 
+        // Start at the first level that is playable but not completed yet
+        let next_level = level_names.iter().position(|level_name| {
+            self.campaign.is_unlocked(level_name, &self.save_data)
+                && !self.save_data.is_completed(level_name)
+        });
+
+        let list_bounds = stack.next(column.h - 50.0);
+        let list = ScrollList::new(
+            ctx,
+            list_bounds,
+            90.0,
+            items,
+            Box::new(move |ctx, index| {
+                let level_name = &level_names[index];
+                let level_info = load_level_info(level_name).ok()?;
+                let view = Box::new(GameView::new(ctx, level_info, Some(level_name.clone())));
+                Some(ViewEvent::Transition(vec![ViewEvent::PushView(view)]))
+            }),
+        )?;
+        ui_layer.add(list.highlight(next_level.unwrap_or(0)));
+
         self.ui_layer = Some(ui_layer);
 
         Ok(())
@@ -64,6 +123,10 @@ impl View for LevelsView {
         self.ui_layer.as_ref().unwrap().draw(ctx, canvas)
     }
 
+    fn resize(&mut self, _ctx: &mut Context) {
+        self.ui_layer = None;
+    }
+
     fn receive_input(&mut self, ctx: &mut Context, input: Input) -> Vec<ViewEvent> {
         match &mut self.ui_layer {
             Some(ui_layer) => {
@@ -76,6 +139,26 @@ impl View for LevelsView {
                     Input::KeyDown {
                         key_code: KeyCode::Escape,
                     } => events.push(ViewEvent::PopView),
+                    Input::GamepadButtonDown { button }
+                        if self.controls.matches_button(Action::MenuBack, button) =>
+                    {
+                        events.push(ViewEvent::PopView)
+                    }
+                    Input::GamepadButtonDown { button }
+                        if self.controls.matches_button(Action::MenuConfirm, button) =>
+                    {
+                        events.extend(ui_layer.activate_focused(ctx))
+                    }
+                    Input::GamepadButtonDown { button } => {
+                        events.extend(ui_layer.gamepad_press(ctx, button))
+                    }
+                    Input::GamepadAxis { axis, value } => {
+                        events.extend(ui_layer.gamepad_axis(ctx, axis, value))
+                    }
+                    Input::KeyDown { key_code } => events.extend(ui_layer.key_press(ctx, key_code)),
+                    Input::MouseMotion { x, y } => events.extend(ui_layer.mouse_motion(ctx, x, y)),
+                    Input::MouseUp { x, y, .. } => events.extend(ui_layer.mouse_release(ctx, x, y)),
+                    Input::MouseWheel { x, y, delta } => ui_layer.mouse_wheel(ctx, x, y, delta),
                     _ => {}
                 };
 
@@ -84,43 +167,153 @@ impl View for LevelsView {
             None => Vec::new(),
         }
     }
+
+    fn resume(&mut self, ctx: &mut Context) {
+        // Progress may have changed while playing, so rebuild the buttons
+        self.save_data = SaveData::load(ctx);
+        self.controls = Controls::load(ctx);
+        self.ui_layer = None;
+    }
 }
 
-fn get_all_level_names() -> Vec<String> {
-    let levels_directory = Path::new("levels");
-    fs::read_dir(levels_directory)
-        .expect("Could not read levels")
-        .map(|level| {
-            level
-                .expect("Failed to read level")
-                .file_name()
-                .into_string()
-                .expect("Could not read level name")
-        })
-        .map(|name| name.replace(".json", ""))
-        .collect()
+fn level_details(
+    level_name: &str,
+    metadata: &LevelMetadata,
+    unlocked: bool,
+    save_data: &SaveData,
+) -> String {
+    if !unlocked {
+        return "Locked".to_string();
+    }
+
+    let mut details = vec![match save_data.record(level_name) {
+        Some(record) => format!(
+            "Best time {:.1}s, rank {}",
+            record.best_time, record.best_rank
+        ),
+        None => "Not completed".to_string(),
+    }];
+    if !metadata.author.is_empty() {
+        details.push(format!("by {}", metadata.author));
+    }
+    if let Some(par_time) = metadata.par_time {
+        details.push(format!("par {:.0}s", par_time));
+    }
+    if let Some(difficulty) = metadata.difficulty {
+        details.push(format!("difficulty {}/5", difficulty));
+    }
+    if !metadata.tags.is_empty() {
+        details.push(metadata.tags.join(", "));
+    }
+    details.join(" - ")
 }
 
-fn level_button(
-    ctx: &mut Context,
-    bounds: Rect,
-    level_name: String,
-) -> GameResult<Button<ViewEvent>> {
-    let display_name = level_display_name(&level_name);
+/// A list item for a level whose file could not be read, which can not be picked
+pub fn unreadable_level_item(level_name: &str) -> ListItem {
+    ListItem::new(&level_display_name(level_name))
+        .details(&format!("Could not read levels/{}.json", level_name))
+        .disabled(true)
+}
+
+/// A level read from its file, with a thumbnail of it
+pub struct CachedLevel {
+    pub info: LevelInfo,
+    pub thumbnail: Image,
+}
 
-    Button::new(
+/// Levels by level name, so each level file is only read and rendered once. Levels whose file
+/// could not be read are remembered as `None`.
+pub struct LevelCache {
+    levels: HashMap<String, Option<CachedLevel>>,
+}
+
+impl LevelCache {
+    pub fn new() -> Self {
+        LevelCache {
+            levels: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, ctx: &mut Context, level_name: &str) -> GameResult<Option<&CachedLevel>> {
+        if !self.levels.contains_key(level_name) {
+            let level = match load_level_info(level_name) {
+                Ok(info) => Some(CachedLevel {
+                    thumbnail: level_thumbnail(ctx, &info)?,
+                    info,
+                }),
+                Err(e) => {
+                    eprintln!("Could not read level {}: {}", level_name, e);
+                    None
+                }
+            };
+            self.levels.insert(level_name.to_string(), level);
+        }
+        Ok(self.levels[level_name].as_ref())
+    }
+
+    /// Forget a level that changed
+    pub fn remove(&mut self, level_name: &str) {
+        self.levels.remove(level_name);
+    }
+}
+
+/// Render a level as it is at the start into a small image
+fn level_thumbnail(ctx: &mut Context, level_info: &LevelInfo) -> GameResult<Image> {
+    let image = Image::new_canvas_image(
         ctx,
-        bounds,
-        Some(&display_name),
-        Box::new(move |_| {
-            let level_info = load_level_info(&level_name);
-            let view = Box::new(GameView::new(level_info));
-            Some(ViewEvent::PushView(view))
-        }),
+        ctx.gfx.surface_format(),
+        THUMBNAIL_SIZE,
+        THUMBNAIL_SIZE,
+        1,
+    );
+    let mut canvas = Canvas::from_image(ctx, image.clone(), Color::BLACK);
+    canvas.set_screen_coordinates(thumbnail_bounds(level_info));
+
+    let game = Game::from_level_info(level_info.clone());
+    Renderer::new().render_preview(ctx, &mut canvas, &game)?;
+
+    canvas.finish(ctx)?;
+    Ok(image)
+}
+
+/// A square around everything in the level, with the level centered in it
+fn thumbnail_bounds(level_info: &LevelInfo) -> Rect {
+    let points = level_info
+        .obstacle_data
+        .iter()
+        .chain(std::iter::once(&level_info.end_area_data))
+        .chain(level_info.extra_end_area_data.iter())
+        .chain(level_info.guard_data.iter().map(|(_, patrol)| patrol))
+        .flatten()
+        .chain(level_info.guard_data.iter().map(|(pos, _)| pos));
+
+    let (mut min_x, mut min_y) = level_info.player_data;
+    let (mut max_x, mut max_y) = level_info.player_data;
+    for &(x, y) in points {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+
+    let size = (max_x - min_x).max(max_y - min_y).max(1.0) * 1.1;
+    Rect::new(
+        (min_x + max_x - size) / 2.0,
+        (min_y + max_y - size) / 2.0,
+        size,
+        size,
     )
 }
 
-fn level_display_name(level_name: &str) -> String {
+/// The title of the level, or a name made from the file name if it has none
+pub fn level_title(level_name: &str, metadata: &LevelMetadata) -> String {
+    match metadata.title.is_empty() {
+        true => level_display_name(level_name),
+        false => metadata.title.clone(),
+    }
+}
+
+pub fn level_display_name(level_name: &str) -> String {
     let display_name = level_name.replace('_', " ");
     let mut display_name_chars = display_name.chars();
     match display_name_chars.next() {
@@ -128,3 +321,58 @@ fn level_display_name(level_name: &str) -> String {
         Some(c) => c.to_uppercase().collect::<String>() + display_name_chars.as_str(),
     }
 }
+
+#[cfg(test)]
+mod levels_tests {
+    use super::{level_details, level_title, thumbnail_bounds};
+    use crate::{
+        campaign::save_data::SaveData,
+        game::level_info::{LevelInfo, LevelMetadata},
+    };
+    use ggez::graphics::Rect;
+
+    #[test]
+    fn title_falls_back_to_file_name() {
+        let mut metadata = LevelMetadata::default();
+        assert_eq!(level_title("the_vault", &metadata), "The vault");
+        metadata.title = String::from("Into the Vault");
+        assert_eq!(level_title("the_vault", &metadata), "Into the Vault");
+    }
+
+    #[test]
+    fn details_show_metadata() {
+        let metadata = LevelMetadata {
+            author: String::from("Sam"),
+            difficulty: Some(3),
+            par_time: Some(45.0),
+            tags: vec![String::from("patrols"), String::from("items")],
+            ..LevelMetadata::default()
+        };
+        let save_data = SaveData::default();
+        assert_eq!(
+            level_details("level_1", &metadata, true, &save_data),
+            "Not completed - by Sam - par 45s - difficulty 3/5 - patrols, items"
+        );
+        assert_eq!(
+            level_details("level_1", &LevelMetadata::default(), false, &save_data),
+            "Locked"
+        );
+    }
+
+    #[test]
+    fn thumbnail_shows_whole_level() {
+        let level_info: LevelInfo = serde_json::from_str(
+            r#"{
+                "player_data": [0.0, 0.0],
+                "guard_data": [[[50.0, 50.0], [[50.0, 50.0], [100.0, 50.0]]]],
+                "obstacle_data": [],
+                "end_area_data": [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0]]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            thumbnail_bounds(&level_info),
+            Rect::new(-5.0, -30.0, 110.0, 110.0)
+        );
+    }
+}
@@ -1,6 +1,12 @@
-use super::{editor::EditorView, levels::LevelsView, View, ViewEvent};
+use super::{editor::EditorView, levels::LevelsView, settings::SettingsView, View, ViewEvent};
 use crate::{
-    gui::{button::Button, label::Label, UiLayer},
+    controls::{Action, Controls},
+    gui::{
+        button::Button,
+        label::Label,
+        layout::{Anchor, VerticalStack},
+        UiLayer,
+    },
     state::Input,
 };
 use ggez::{
@@ -10,12 +16,16 @@ use ggez::{
 };
 
 pub struct MainMenuView {
+    controls: Controls,
     ui_layer: Option<UiLayer<ViewEvent>>,
 }
 
 impl MainMenuView {
-    pub fn new(_ctx: &mut Context) -> GameResult<Self> {
-        Ok(MainMenuView { ui_layer: None })
+    pub fn new(ctx: &mut Context) -> GameResult<Self> {
+        Ok(MainMenuView {
+            controls: Controls::load(ctx),
+            ui_layer: None,
+        })
     }
 
     fn init_ui(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
@@ -23,10 +33,26 @@ impl MainMenuView {
 
         let screen_coords = canvas.screen_coordinates().unwrap();
 
-        ui_layer.add(title_label(ctx, screen_coords)?);
-        ui_layer.add(start_button(ctx, screen_coords)?);
-        ui_layer.add(editor_button(ctx, screen_coords)?);
-        ui_layer.add(quit_button(ctx, screen_coords)?);
+        let title_bounds = Anchor::Top.place(
+            screen_coords,
+            screen_coords.w - 60.0,
+            screen_coords.h / 4.0,
+            screen_coords.h / 4.0,
+        );
+        ui_layer.add(Label::new(ctx, "Stealth Game!", title_bounds));
+
+        // The buttons fill the lower half of the screen
+        let column = Anchor::Bottom.place(
+            screen_coords,
+            screen_coords.w / 2.0,
+            screen_coords.h / 2.0,
+            0.0,
+        );
+        let mut stack = VerticalStack::new(column, 10.0);
+        ui_layer.add(start_button(ctx, stack.next(60.0))?);
+        ui_layer.add(editor_button(ctx, stack.next(60.0))?);
+        ui_layer.add(settings_button(ctx, stack.next(60.0))?);
+        ui_layer.add(quit_button(ctx, stack.next(60.0))?);
 
         self.ui_layer = Some(ui_layer);
 
@@ -47,47 +73,54 @@ impl View for MainMenuView {
         self.ui_layer.as_ref().unwrap().draw(ctx, canvas)
     }
 
+    fn resize(&mut self, _ctx: &mut Context) {
+        self.ui_layer = None;
+    }
+
     fn receive_input(&mut self, ctx: &mut Context, input: Input) -> Vec<ViewEvent> {
         let mut events = Vec::new();
 
-        match &mut self.ui_layer {
-            Some(ui_layer) => {
-                match input {
-                    Input::MouseDown { button, x, y } => {
-                        events.extend(ui_layer.mouse_press(ctx, button, x, y))
-                    }
-                    Input::KeyDown {
-                        key_code: KeyCode::Escape,
-                    } => events.push(ViewEvent::PopView),
-                    _ => {}
-                };
-            }
-            None => {}
+        if let Some(ui_layer) = &mut self.ui_layer {
+            match input {
+                Input::MouseDown { button, x, y } => {
+                    events.extend(ui_layer.mouse_press(ctx, button, x, y))
+                }
+                Input::KeyDown {
+                    key_code: KeyCode::Escape,
+                } => events.push(ViewEvent::PopView),
+                Input::GamepadButtonDown { button }
+                    if self.controls.matches_button(Action::MenuBack, button) =>
+                {
+                    events.push(ViewEvent::PopView)
+                }
+                Input::GamepadButtonDown { button }
+                    if self.controls.matches_button(Action::MenuConfirm, button) =>
+                {
+                    events.extend(ui_layer.activate_focused(ctx))
+                }
+                Input::GamepadButtonDown { button } => {
+                    events.extend(ui_layer.gamepad_press(ctx, button))
+                }
+                Input::GamepadAxis { axis, value } => {
+                    events.extend(ui_layer.gamepad_axis(ctx, axis, value))
+                }
+                Input::KeyDown { key_code } => events.extend(ui_layer.key_press(ctx, key_code)),
+                Input::MouseMotion { x, y } => events.extend(ui_layer.mouse_motion(ctx, x, y)),
+                Input::MouseUp { x, y, .. } => events.extend(ui_layer.mouse_release(ctx, x, y)),
+                _ => {}
+            };
         }
 
         events
     }
-}
 
-fn title_label(ctx: &mut Context, screen_coords: Rect) -> GameResult<Label<ViewEvent>> {
-    let bounds = Rect {
-        x: screen_coords.x + 30.0,
-        y: screen_coords.y + screen_coords.h / 4.0,
-        w: screen_coords.w - 60.0,
-        h: screen_coords.h / 4.0,
-    };
-    let label = Label::new(ctx, "Stealth Game!", bounds);
-    Ok(label)
+    fn resume(&mut self, ctx: &mut Context) {
+        // The controls may have been changed in the settings
+        self.controls = Controls::load(ctx);
+    }
 }
 
-fn start_button(ctx: &mut Context, screen_coords: Rect) -> GameResult<Button<ViewEvent>> {
-    let bounds = Rect {
-        x: screen_coords.x + screen_coords.w / 4.0,
-        y: screen_coords.y + screen_coords.h / 2.0,
-        w: screen_coords.w / 2.0,
-        h: 60.0,
-    };
-
+fn start_button(ctx: &mut Context, bounds: Rect) -> GameResult<Button<ViewEvent>> {
     Button::new(
         ctx,
         bounds,
@@ -99,14 +132,7 @@ fn start_button(ctx: &mut Context, screen_coords: Rect) -> GameResult<Button<Vie
     )
 }
 
-fn editor_button(ctx: &mut Context, screen_coords: Rect) -> GameResult<Button<ViewEvent>> {
-    let bounds = Rect {
-        x: screen_coords.x + screen_coords.w / 4.0,
-        y: screen_coords.y + screen_coords.h / 2.0 + 70.0,
-        w: screen_coords.w / 2.0,
-        h: 60.0,
-    };
-
+fn editor_button(ctx: &mut Context, bounds: Rect) -> GameResult<Button<ViewEvent>> {
     Button::new(
         ctx,
         bounds,
@@ -118,14 +144,16 @@ fn editor_button(ctx: &mut Context, screen_coords: Rect) -> GameResult<Button<Vi
     )
 }
 
-fn quit_button(ctx: &mut Context, screen_coords: Rect) -> GameResult<Button<ViewEvent>> {
-    let bounds = Rect {
-        x: screen_coords.x + screen_coords.w / 4.0,
-        y: screen_coords.y + (screen_coords.h / 2.0) + 140.0,
-        w: screen_coords.w / 2.0,
-        h: 60.0,
-    };
+fn settings_button(ctx: &mut Context, bounds: Rect) -> GameResult<Button<ViewEvent>> {
+    Button::new(
+        ctx,
+        bounds,
+        Some("Settings"),
+        Box::new(|ctx| Some(SettingsView::push(ctx))),
+    )
+}
 
+fn quit_button(ctx: &mut Context, bounds: Rect) -> GameResult<Button<ViewEvent>> {
     Button::new(
         ctx,
         bounds,
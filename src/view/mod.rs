@@ -1,7 +1,11 @@
+pub mod dialog;
 pub mod editor;
 pub mod game;
 pub mod levels;
 pub mod main_menu;
+pub mod pause;
+pub mod result;
+pub mod settings;
 
 use crate::state::Input;
 use ggez::{graphics::Canvas, Context, GameResult};
@@ -9,10 +13,29 @@ use ggez::{graphics::Canvas, Context, GameResult};
 pub enum ViewEvent {
     PopView,
     PushView(Box<dyn View>),
+    /// Pop the top view and push another one, without resuming the view below
+    ReplaceView(Box<dyn View>),
+    /// Pop every view except the bottom one, i.e. go back to the main menu
+    PopToRoot,
+    /// Fade the screen to black, apply the events, and fade back in
+    Transition(Vec<ViewEvent>),
+    /// Show a short message in the corner of the screen, which stays when views change
+    ShowToast(String),
 }
 
 pub trait View {
     fn tick(&mut self, ctx: &mut Context) -> Vec<ViewEvent>;
     fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult;
     fn receive_input(&mut self, ctx: &mut Context, input: Input) -> Vec<ViewEvent>;
+
+    /// Called when the view becomes the top view again after the view above it was popped
+    fn resume(&mut self, _ctx: &mut Context) {}
+
+    /// Called when the window is resized, so the view can lay out its UI again
+    fn resize(&mut self, _ctx: &mut Context) {}
+
+    /// Whether the view below this one is drawn first and shows through, e.g. for dialogs
+    fn is_transparent(&self) -> bool {
+        false
+    }
 }
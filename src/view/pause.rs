@@ -0,0 +1,190 @@
+use super::{game::GameView, settings::SettingsView, View, ViewEvent};
+use crate::{
+    controls::{Action, Controls},
+    game::level_info::LevelInfo,
+    gui::{
+        button::Button,
+        label::Label,
+        layout::{Anchor, VerticalStack},
+        UiLayer,
+    },
+    state::Input,
+};
+use ggez::{
+    graphics::{Canvas, Color, DrawParam, Quad, Rect},
+    Context, GameResult,
+};
+
+const DIM: Color = Color::new(0.0, 0.0, 0.0, 0.6);
+
+#[derive(Clone, Copy)]
+enum PauseEvent {
+    Resume,
+    Restart,
+    Settings,
+    Quit,
+}
+
+/// Pushed on top of a `GameView`, which is drawn dimmed below it but not ticked while this
+/// view is open
+pub struct PauseView {
+    level_info: LevelInfo,
+    level_name: Option<String>,
+    controls: Controls,
+    ui_layer: Option<UiLayer<PauseEvent>>,
+}
+
+impl PauseView {
+    pub fn new(level_info: LevelInfo, level_name: Option<String>, controls: Controls) -> Self {
+        PauseView {
+            level_info,
+            level_name,
+            controls,
+            ui_layer: None,
+        }
+    }
+
+    fn init_ui(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let mut ui_layer = UiLayer::new();
+
+        let screen_coords = canvas.screen_coordinates().unwrap();
+
+        let title_bounds = Anchor::Top.place(
+            screen_coords,
+            screen_coords.w / 2.0,
+            screen_coords.h / 8.0,
+            screen_coords.h / 8.0,
+        );
+        ui_layer.add(Label::new(ctx, "Paused", title_bounds));
+
+        let buttons = [
+            ("Resume", PauseEvent::Resume),
+            ("Restart", PauseEvent::Restart),
+            ("Settings", PauseEvent::Settings),
+            ("Quit to menu", PauseEvent::Quit),
+        ];
+
+        let column = Rect::new(
+            screen_coords.x + screen_coords.w / 4.0,
+            screen_coords.y + screen_coords.h / 2.0 - 70.0,
+            screen_coords.w / 2.0,
+            screen_coords.h / 2.0 + 70.0,
+        );
+        let mut stack = VerticalStack::new(column, 10.0);
+        for (text, event) in buttons {
+            ui_layer.add(Button::new(
+                ctx,
+                stack.next(60.0),
+                Some(text),
+                Box::new(move |_| Some(event)),
+            )?);
+        }
+
+        self.ui_layer = Some(ui_layer);
+
+        Ok(())
+    }
+
+    fn handle_pause_events(&self, ctx: &mut Context, events: Vec<PauseEvent>) -> Vec<ViewEvent> {
+        let mut view_events = Vec::new();
+
+        for event in events {
+            match event {
+                PauseEvent::Resume => view_events.push(ViewEvent::PopView),
+                PauseEvent::Restart => {
+                    let view = GameView::new(ctx, self.level_info.clone(), self.level_name.clone());
+                    view_events.push(ViewEvent::Transition(vec![
+                        ViewEvent::PopView,
+                        ViewEvent::ReplaceView(Box::new(view)),
+                    ]));
+                }
+                PauseEvent::Settings => {
+                    view_events.push(SettingsView::push(ctx));
+                }
+                PauseEvent::Quit => {
+                    let events = match self.level_name {
+                        Some(_) => vec![ViewEvent::PopToRoot],
+                        // Previews go back to the editor
+                        None => vec![ViewEvent::PopView, ViewEvent::PopView],
+                    };
+                    view_events.push(ViewEvent::Transition(events));
+                }
+            }
+        }
+
+        view_events
+    }
+}
+
+impl View for PauseView {
+    fn tick(&mut self, _ctx: &mut Context) -> Vec<ViewEvent> {
+        Vec::new()
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        if self.ui_layer.is_none() {
+            self.init_ui(ctx, canvas)?;
+        }
+
+        let screen_coords = canvas.screen_coordinates().unwrap();
+        canvas.draw(&Quad, DrawParam::new().dest_rect(screen_coords).color(DIM));
+
+        self.ui_layer.as_ref().unwrap().draw(ctx, canvas)
+    }
+
+    fn resize(&mut self, _ctx: &mut Context) {
+        self.ui_layer = None;
+    }
+
+    fn is_transparent(&self) -> bool {
+        true
+    }
+
+    fn receive_input(&mut self, ctx: &mut Context, input: Input) -> Vec<ViewEvent> {
+        let mut events = Vec::new();
+
+        if let Some(ui_layer) = &mut self.ui_layer {
+            match input {
+                Input::MouseDown { button, x, y } => {
+                    events.extend(ui_layer.mouse_press(ctx, button, x, y))
+                }
+                Input::KeyDown { key_code }
+                    if self.controls.matches_key(Action::Pause, key_code) =>
+                {
+                    events.push(PauseEvent::Resume)
+                }
+                Input::GamepadButtonDown { button }
+                    if self.controls.matches_button(Action::Pause, button) =>
+                {
+                    events.push(PauseEvent::Resume)
+                }
+                Input::GamepadButtonDown { button }
+                    if self.controls.matches_button(Action::MenuBack, button) =>
+                {
+                    events.push(PauseEvent::Resume)
+                }
+                Input::GamepadButtonDown { button }
+                    if self.controls.matches_button(Action::MenuConfirm, button) =>
+                {
+                    events.extend(ui_layer.activate_focused(ctx))
+                }
+                Input::GamepadButtonDown { button } => {
+                    events.extend(ui_layer.gamepad_press(ctx, button))
+                }
+                Input::GamepadAxis { axis, value } => {
+                    events.extend(ui_layer.gamepad_axis(ctx, axis, value))
+                }
+                Input::KeyDown { key_code } => events.extend(ui_layer.key_press(ctx, key_code)),
+                Input::MouseMotion { x, y } => events.extend(ui_layer.mouse_motion(ctx, x, y)),
+                Input::MouseUp { x, y, .. } => events.extend(ui_layer.mouse_release(ctx, x, y)),
+                _ => {}
+            };
+        }
+
+        self.handle_pause_events(ctx, events)
+    }
+
+    fn resume(&mut self, ctx: &mut Context) {
+        self.controls = Controls::load(ctx);
+    }
+}
@@ -0,0 +1,280 @@
+use super::{
+    dialog::DialogView,
+    game::{load_level_info, GameView},
+    levels::level_title,
+    View, ViewEvent,
+};
+use crate::{
+    campaign::{save_data::SaveData, Campaign},
+    controls::{Action, Controls},
+    game::{level_info::LevelInfo, stats::RunStats, Outcome},
+    gui::{
+        button::Button,
+        label::Label,
+        layout::{Anchor, VerticalStack},
+        UiLayer,
+    },
+    state::Input,
+};
+use ggez::{
+    graphics::{Canvas, Rect},
+    input::keyboard::KeyCode,
+    Context, GameResult,
+};
+
+#[derive(Clone, Copy)]
+enum ResultEvent {
+    Retry,
+    NextLevel,
+    Menu,
+}
+
+/// Shown after a level ends, with the statistics of the run
+pub struct ResultView {
+    outcome: Outcome,
+    stats: RunStats,
+    level_info: LevelInfo,
+    level_name: Option<String>,
+    next_level: Option<String>,
+    /// The next level can only be started after winning, once it is unlocked
+    next_level_playable: bool,
+    controls: Controls,
+    ui_layer: Option<UiLayer<ResultEvent>>,
+}
+
+impl ResultView {
+    pub fn new(
+        ctx: &mut Context,
+        outcome: Outcome,
+        stats: RunStats,
+        level_info: LevelInfo,
+        level_name: Option<String>,
+    ) -> Self {
+        let campaign = Campaign::load();
+        let next_level = level_name
+            .as_ref()
+            .and_then(|name| campaign.next_level(name));
+        let next_level_playable = match (&next_level, outcome) {
+            (Some(next), Outcome::Won) => campaign.is_unlocked(next, &SaveData::load(ctx)),
+            _ => false,
+        };
+
+        ResultView {
+            outcome,
+            stats,
+            level_info,
+            level_name,
+            next_level,
+            next_level_playable,
+            controls: Controls::load(ctx),
+            ui_layer: None,
+        }
+    }
+
+    fn init_ui(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let mut ui_layer = UiLayer::new();
+
+        let screen_coords = canvas.screen_coordinates().unwrap();
+
+        let title = match self.outcome {
+            Outcome::Won => "Level complete!",
+            Outcome::Discovered => "You were discovered...",
+            Outcome::ObjectiveFailed => "Mission failed...",
+        };
+        let title_bounds = Anchor::Top.place(
+            screen_coords,
+            screen_coords.w - 60.0,
+            screen_coords.h / 6.0,
+            30.0,
+        );
+        ui_layer.add(Label::new(ctx, title, title_bounds));
+
+        let level_title = match &self.level_name {
+            Some(name) => level_title(name, &self.level_info.metadata),
+            None => String::from("Preview"),
+        };
+        let stat_lines = [
+            level_title,
+            time_line(&self.stats, self.level_info.metadata.par_time),
+            format!("Times spotted: {}", self.stats.times_spotted),
+            format!("Distance travelled: {:.0}", self.stats.distance_travelled),
+            format!(
+                "Objectives completed: {}/{}",
+                self.stats.objectives_completed, self.stats.objectives_total
+            ),
+        ];
+        let stats_bounds = Rect::new(
+            screen_coords.x + screen_coords.w / 4.0,
+            screen_coords.y + screen_coords.h / 6.0 + 60.0,
+            screen_coords.w / 2.0,
+            40.0 * stat_lines.len() as f32,
+        );
+        let mut stack = VerticalStack::new(stats_bounds, 10.0);
+        for line in &stat_lines {
+            ui_layer.add(Label::new(ctx, line, stack.next(30.0)));
+        }
+
+        let mut buttons = vec![("Retry", ResultEvent::Retry, true)];
+        if self.next_level.is_some() {
+            buttons.push((
+                "Next level",
+                ResultEvent::NextLevel,
+                self.next_level_playable,
+            ));
+        }
+        buttons.push(("Back to menu", ResultEvent::Menu, true));
+
+        // The buttons are stacked at the bottom of the screen
+        let column = Anchor::Bottom.place(
+            screen_coords,
+            screen_coords.w / 2.0,
+            70.0 * buttons.len() as f32,
+            0.0,
+        );
+        let mut stack = VerticalStack::new(column, 10.0);
+        for (text, event, enabled) in buttons {
+            let button = result_button(ctx, stack.next(60.0), text, event)?;
+            ui_layer.add(button.disabled(!enabled));
+        }
+
+        self.ui_layer = Some(ui_layer);
+
+        Ok(())
+    }
+
+    fn handle_result_events(&self, ctx: &Context, events: Vec<ResultEvent>) -> Vec<ViewEvent> {
+        let mut view_events = Vec::new();
+
+        for event in events {
+            match event {
+                ResultEvent::Retry => {
+                    let view = GameView::new(ctx, self.level_info.clone(), self.level_name.clone());
+                    view_events.push(ViewEvent::Transition(vec![ViewEvent::ReplaceView(
+                        Box::new(view),
+                    )]));
+                }
+                ResultEvent::NextLevel => {
+                    if let Some(next_level) = &self.next_level {
+                        match load_level_info(next_level) {
+                            Ok(level_info) => {
+                                let view =
+                                    GameView::new(ctx, level_info, Some(next_level.to_owned()));
+                                view_events.push(ViewEvent::Transition(vec![
+                                    ViewEvent::ReplaceView(Box::new(view)),
+                                ]));
+                            }
+                            Err(e) => {
+                                let dialog =
+                                    DialogView::message("Could not open level", &e.to_string());
+                                view_events.push(ViewEvent::PushView(Box::new(dialog)));
+                            }
+                        }
+                    }
+                }
+                ResultEvent::Menu => {
+                    view_events.push(ViewEvent::Transition(vec![match self.level_name {
+                        Some(_) => ViewEvent::PopToRoot,
+                        // Previews go back to the editor
+                        None => ViewEvent::PopView,
+                    }]))
+                }
+            }
+        }
+
+        view_events
+    }
+}
+
+impl View for ResultView {
+    fn tick(&mut self, _ctx: &mut Context) -> Vec<ViewEvent> {
+        Vec::new()
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        if self.ui_layer.is_none() {
+            self.init_ui(ctx, canvas)?;
+        }
+
+        self.ui_layer.as_ref().unwrap().draw(ctx, canvas)
+    }
+
+    fn resize(&mut self, _ctx: &mut Context) {
+        self.ui_layer = None;
+    }
+
+    fn receive_input(&mut self, ctx: &mut Context, input: Input) -> Vec<ViewEvent> {
+        let mut events = Vec::new();
+
+        if let Some(ui_layer) = &mut self.ui_layer {
+            match input {
+                Input::MouseDown { button, x, y } => {
+                    events.extend(ui_layer.mouse_press(ctx, button, x, y))
+                }
+                Input::KeyDown {
+                    key_code: KeyCode::Escape,
+                } => events.push(ResultEvent::Menu),
+                Input::GamepadButtonDown { button }
+                    if self.controls.matches_button(Action::MenuBack, button) =>
+                {
+                    events.push(ResultEvent::Menu)
+                }
+                Input::GamepadButtonDown { button }
+                    if self.controls.matches_button(Action::MenuConfirm, button) =>
+                {
+                    events.extend(ui_layer.activate_focused(ctx))
+                }
+                Input::GamepadButtonDown { button } => {
+                    events.extend(ui_layer.gamepad_press(ctx, button))
+                }
+                Input::GamepadAxis { axis, value } => {
+                    events.extend(ui_layer.gamepad_axis(ctx, axis, value))
+                }
+                Input::KeyDown { key_code } => events.extend(ui_layer.key_press(ctx, key_code)),
+                Input::MouseMotion { x, y } => events.extend(ui_layer.mouse_motion(ctx, x, y)),
+                Input::MouseUp { x, y, .. } => events.extend(ui_layer.mouse_release(ctx, x, y)),
+                _ => {}
+            };
+        }
+
+        self.handle_result_events(ctx, events)
+    }
+}
+
+fn result_button(
+    ctx: &mut Context,
+    bounds: Rect,
+    text: &str,
+    event: ResultEvent,
+) -> GameResult<Button<ResultEvent>> {
+    Button::new(ctx, bounds, Some(text), Box::new(move |_| Some(event)))
+}
+
+/// The time of the run, compared to the par time of the level if it has one
+fn time_line(stats: &RunStats, par_time: Option<f32>) -> String {
+    let time = stats.time;
+    match par_time {
+        Some(par_time) if time <= par_time => {
+            format!("Time: {:.1} s, under par ({:.0} s)", time, par_time)
+        }
+        Some(par_time) => format!("Time: {:.1} s, par {:.0} s", time, par_time),
+        None => format!("Time: {:.1} s", time),
+    }
+}
+
+#[cfg(test)]
+mod result_tests {
+    use super::time_line;
+    use crate::game::stats::RunStats;
+
+    #[test]
+    fn time_is_compared_to_par() {
+        let mut stats = RunStats::new();
+        stats.time = 30.0;
+        assert_eq!(time_line(&stats, None), "Time: 30.0 s");
+        assert_eq!(
+            time_line(&stats, Some(45.0)),
+            "Time: 30.0 s, under par (45 s)"
+        );
+        assert_eq!(time_line(&stats, Some(20.0)), "Time: 30.0 s, par 20 s");
+    }
+}
@@ -0,0 +1,367 @@
+use super::{dialog::DialogView, View, ViewEvent};
+use crate::{
+    controls::{Action, ActionContext, Controls, MAX_STICK_DEADZONE},
+    gui::{
+        button::Button,
+        dropdown::Dropdown,
+        label::Label,
+        layout::{Anchor, HorizontalStack, VerticalStack},
+        slider::Slider,
+        UiLayer,
+    },
+    preferences::{Difficulty, Preferences},
+    state::Input,
+};
+use ggez::{
+    graphics::{Canvas, Rect},
+    input::keyboard::KeyCode,
+    Context, GameResult,
+};
+
+#[derive(Clone, Copy)]
+enum SettingsEvent {
+    Rebind(Action),
+    SetStickDeadzone(f32),
+    SetVolume(f32),
+    SetDifficulty(Difficulty),
+    ResetDefaults,
+    Back,
+}
+
+/// Lets the player rebind the controls and change the preferences, which are saved when
+/// leaving the view
+pub struct SettingsView {
+    controls: Controls,
+    preferences: Preferences,
+    waiting_for_input: Option<Action>,
+    message: Option<String>,
+    /// The position of the focused widget, to focus it again when the widgets are made again
+    focus: Option<usize>,
+    ui_layer: Option<UiLayer<SettingsEvent>>,
+}
+
+impl SettingsView {
+    pub fn new(ctx: &mut Context) -> GameResult<Self> {
+        Ok(SettingsView {
+            controls: Controls::load(ctx),
+            preferences: Preferences::load(ctx),
+            waiting_for_input: None,
+            message: None,
+            focus: None,
+            ui_layer: None,
+        })
+    }
+
+    /// Push the settings, or a dialog saying why they could not be opened
+    pub fn push(ctx: &mut Context) -> ViewEvent {
+        let view: Box<dyn View> = match SettingsView::new(ctx) {
+            Ok(view) => Box::new(view),
+            Err(e) => Box::new(DialogView::message(
+                "Could not open settings",
+                &e.to_string(),
+            )),
+        };
+        ViewEvent::PushView(view)
+    }
+
+    fn init_ui(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let mut ui_layer = UiLayer::new();
+
+        let screen_coords = canvas.screen_coordinates().unwrap();
+        let content = Rect::new(
+            screen_coords.x + 20.0,
+            screen_coords.y + 20.0,
+            screen_coords.w - 40.0,
+            screen_coords.h - 40.0,
+        );
+        let columns = HorizontalStack::split(content, 3, 20.0);
+
+        let mut stacks: Vec<VerticalStack> = columns
+            .iter()
+            .map(|column| VerticalStack::new(*column, 10.0))
+            .collect();
+        for action in Action::ALL {
+            let column = match action.context() {
+                ActionContext::Game => 0,
+                ActionContext::Editor => 1,
+                ActionContext::Menu => 2,
+            };
+            let text = self.binding_text(action);
+            ui_layer.add(settings_button(
+                ctx,
+                stacks[column].next(35.0),
+                &text,
+                SettingsEvent::Rebind(action),
+            )?);
+        }
+
+        // The preferences and stick settings go below the menu actions
+        let stack = &mut stacks[2];
+        stack.skip(10.0);
+        ui_layer.add(Label::new(ctx, "Difficulty", stack.next(30.0)));
+        let difficulty_bounds = stack.next(35.0);
+        stack.skip(10.0);
+
+        ui_layer.add(Label::new(ctx, "Stick deadzone", stack.next(30.0)));
+        let deadzone = Slider::new(
+            stack.next(30.0),
+            0.0,
+            MAX_STICK_DEADZONE,
+            self.controls.stick_deadzone(),
+            Box::new(|deadzone| Some(SettingsEvent::SetStickDeadzone(deadzone))),
+        )
+        .step(0.05);
+        ui_layer.add(deadzone);
+        stack.skip(10.0);
+
+        ui_layer.add(Label::new(ctx, "Volume", stack.next(30.0)));
+        let volume = Slider::new(
+            stack.next(30.0),
+            0.0,
+            1.0,
+            self.preferences.volume,
+            Box::new(|volume| Some(SettingsEvent::SetVolume(volume))),
+        )
+        .step(0.05);
+        ui_layer.add(volume);
+
+        let bottom = Anchor::Bottom.place(content, content.w, 60.0, 0.0);
+        if let Some(message) = &self.message {
+            let bounds = Rect::new(bottom.x, bottom.y - 50.0, bottom.w, 30.0);
+            ui_layer.add(Label::new(ctx, message, bounds));
+        }
+
+        let mut buttons = HorizontalStack::split(bottom, 2, 20.0).into_iter();
+        ui_layer.add(settings_button(
+            ctx,
+            buttons.next().unwrap(),
+            "Reset to defaults",
+            SettingsEvent::ResetDefaults,
+        )?);
+        ui_layer.add(settings_button(
+            ctx,
+            buttons.next().unwrap(),
+            "Back",
+            SettingsEvent::Back,
+        )?);
+
+        // The difficulty list opens over the widgets below it, so it is added last
+        let selected = Difficulty::ALL
+            .iter()
+            .position(|&d| d == self.preferences.difficulty)
+            .unwrap_or_default();
+        ui_layer.add(Dropdown::new(
+            ctx,
+            difficulty_bounds,
+            Difficulty::ALL
+                .iter()
+                .map(|d| d.name().to_string())
+                .collect(),
+            selected,
+            Box::new(|i| Some(SettingsEvent::SetDifficulty(Difficulty::ALL[i]))),
+        )?);
+
+        ui_layer.restore_focus(self.focus.take());
+        self.ui_layer = Some(ui_layer);
+
+        Ok(())
+    }
+
+    fn binding_text(&self, action: Action) -> String {
+        if self.waiting_for_input == Some(action) {
+            return match action.context() {
+                ActionContext::Menu => format!("{}: press a button...", action.name()),
+                _ => format!("{}: press a key or button...", action.name()),
+            };
+        }
+
+        let binding = self.controls.binding(action);
+        if action.context() == ActionContext::Menu {
+            let button = binding
+                .button
+                .map_or(String::from("-"), |button| format!("{:?}", button));
+            return format!("{}: {}", action.name(), button);
+        }
+
+        let key = match binding.key {
+            Some(key) if action.needs_ctrl() => format!("Ctrl+{:?}", key),
+            Some(key) => format!("{:?}", key),
+            None => String::from("-"),
+        };
+        match binding.button {
+            Some(button) => format!("{}: {} / {:?}", action.name(), key, button),
+            None => format!("{}: {}", action.name(), key),
+        }
+    }
+
+    fn handle_settings_events(
+        &mut self,
+        ctx: &Context,
+        events: Vec<SettingsEvent>,
+    ) -> Vec<ViewEvent> {
+        let mut view_events = Vec::new();
+
+        for event in events {
+            match event {
+                SettingsEvent::Rebind(action) => {
+                    // Clicking the same action again cancels the rebinding
+                    self.waiting_for_input = match self.waiting_for_input {
+                        Some(waiting) if waiting == action => None,
+                        _ => Some(action),
+                    };
+                    self.message = None;
+                }
+                // Keep the widgets for these, so the player can keep dragging
+                SettingsEvent::SetStickDeadzone(deadzone) => {
+                    self.controls.set_stick_deadzone(deadzone);
+                    continue;
+                }
+                SettingsEvent::SetVolume(volume) => {
+                    self.preferences.volume = volume;
+                    continue;
+                }
+                SettingsEvent::SetDifficulty(difficulty) => {
+                    self.preferences.difficulty = difficulty;
+                    continue;
+                }
+                SettingsEvent::ResetDefaults => {
+                    self.controls = Controls::default();
+                    self.preferences = Preferences::default();
+                    self.waiting_for_input = None;
+                    self.message = Some(String::from("Settings reset to defaults"));
+                }
+                SettingsEvent::Back => {
+                    if let Err(e) = self.controls.save(ctx) {
+                        let message = format!("Could not save controls: {}", e);
+                        view_events.push(ViewEvent::ShowToast(message));
+                    }
+                    if let Err(e) = self.preferences.save(ctx) {
+                        let message = format!("Could not save preferences: {}", e);
+                        view_events.push(ViewEvent::ShowToast(message));
+                    }
+                    view_events.push(ViewEvent::PopView);
+                }
+            }
+
+            // Rebuild the buttons to show the new bindings
+            self.rebuild_ui();
+        }
+
+        view_events
+    }
+
+    /// Stop waiting for input if the binding succeeded, otherwise tell the player about the conflict
+    fn finish_rebind(&mut self, result: Result<(), Action>) {
+        match result {
+            Ok(()) => {
+                self.waiting_for_input = None;
+                self.message = None;
+            }
+            Err(conflict) => {
+                self.message = Some(format!(
+                    "Already used by \"{}\", choose another",
+                    conflict.name()
+                ))
+            }
+        }
+        self.rebuild_ui();
+    }
+
+    /// Make the widgets again on the next draw, keeping the focus on the same widget so the
+    /// view stays usable with the keyboard or a gamepad
+    fn rebuild_ui(&mut self) {
+        if let Some(ui_layer) = self.ui_layer.take() {
+            self.focus = ui_layer.focus_position();
+        }
+    }
+}
+
+impl View for SettingsView {
+    fn tick(&mut self, _ctx: &mut Context) -> Vec<ViewEvent> {
+        Vec::new()
+    }
+
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        if self.ui_layer.is_none() {
+            self.init_ui(ctx, canvas)?;
+        }
+
+        self.ui_layer.as_ref().unwrap().draw(ctx, canvas)
+    }
+
+    fn resize(&mut self, _ctx: &mut Context) {
+        self.rebuild_ui();
+    }
+
+    fn receive_input(&mut self, ctx: &mut Context, input: Input) -> Vec<ViewEvent> {
+        let mut events = Vec::new();
+
+        if let Some(action) = self.waiting_for_input {
+            match input {
+                // Ctrl is held for these anyway, so it is the key pressed with it that is bound
+                Input::KeyDown {
+                    key_code: KeyCode::LControl | KeyCode::RControl | KeyCode::LWin | KeyCode::RWin,
+                } if action.needs_ctrl() => return Vec::new(),
+                Input::KeyDown { .. } if action.context() == ActionContext::Menu => {
+                    self.waiting_for_input = None;
+                    self.message = Some(String::from("Menu actions only use gamepad buttons"));
+                    self.rebuild_ui();
+                    return Vec::new();
+                }
+                Input::KeyDown { key_code } => {
+                    let result = self.controls.bind_key(action, key_code);
+                    self.finish_rebind(result);
+                    return Vec::new();
+                }
+                Input::GamepadButtonDown { button } => {
+                    let result = self.controls.bind_button(action, button);
+                    self.finish_rebind(result);
+                    return Vec::new();
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(ui_layer) = &mut self.ui_layer {
+            match input {
+                Input::MouseDown { button, x, y } => {
+                    events.extend(ui_layer.mouse_press(ctx, button, x, y))
+                }
+                Input::KeyDown {
+                    key_code: KeyCode::Escape,
+                } => events.push(SettingsEvent::Back),
+                Input::GamepadButtonDown { button }
+                    if self.controls.matches_button(Action::MenuBack, button) =>
+                {
+                    events.push(SettingsEvent::Back)
+                }
+                Input::GamepadButtonDown { button }
+                    if self.controls.matches_button(Action::MenuConfirm, button) =>
+                {
+                    events.extend(ui_layer.activate_focused(ctx))
+                }
+                Input::GamepadButtonDown { button } => {
+                    events.extend(ui_layer.gamepad_press(ctx, button))
+                }
+                Input::GamepadAxis { axis, value } => {
+                    events.extend(ui_layer.gamepad_axis(ctx, axis, value))
+                }
+                Input::KeyDown { key_code } => events.extend(ui_layer.key_press(ctx, key_code)),
+                Input::MouseMotion { x, y } => events.extend(ui_layer.mouse_motion(ctx, x, y)),
+                Input::MouseUp { x, y, .. } => events.extend(ui_layer.mouse_release(ctx, x, y)),
+                _ => {}
+            };
+        }
+
+        self.handle_settings_events(ctx, events)
+    }
+}
+
+fn settings_button(
+    ctx: &mut Context,
+    bounds: Rect,
+    text: &str,
+    event: SettingsEvent,
+) -> GameResult<Button<SettingsEvent>> {
+    Button::new(ctx, bounds, Some(text), Box::new(move |_| Some(event)))
+}